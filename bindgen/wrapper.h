@@ -4,5 +4,6 @@ typedef struct _IO_FILE FILE;
 #include <babeltrace2/babeltrace.h>
 #include "common/metadata/decoder.h"
 #include "common/msg-iter/msg-iter.h"
+#include "logging/log.h"
 #include "lib/graph/component-class.h"
 #include "lib/graph/component.h"
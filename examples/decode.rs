@@ -11,6 +11,8 @@ use std::{process, thread};
 use structopt::StructOpt;
 use url::Url;
 
+babeltrace2_sys::register_static_plugins!();
+
 #[derive(StructOpt, Debug)]
 struct Opts {
     #[structopt(subcommand)]
@@ -42,6 +44,14 @@ enum Cmd {
         #[structopt(long = "unix-epoch")]
         force_clock_class_origin_unix_epoch: Option<bool>,
 
+        /// Discard all the events before this time, see filter.utils.trimmer for the time formats
+        #[structopt(long)]
+        begin: Option<String>,
+
+        /// Discard all the events after this time, see filter.utils.trimmer for the time formats
+        #[structopt(long)]
+        end: Option<String>,
+
         /// Path to trace directories
         #[structopt(name = "input", required = true, min_values = 1)]
         inputs: Vec<PathBuf>,
@@ -84,6 +94,13 @@ enum Cmd {
         #[structopt(verbatim_doc_comment)]
         url: Url,
     },
+
+    /// List the available plugins and their component classes
+    ListPlugins {
+        /// Also load the plugins found in this directory, recursively
+        #[structopt(long = "plugin-dir")]
+        plugin_dirs: Vec<PathBuf>,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -119,6 +136,8 @@ fn do_main() -> Result<(), Box<dyn std::error::Error>> {
             clock_class_offset_ns,
             clock_class_offset_s,
             force_clock_class_origin_unix_epoch,
+            begin,
+            end,
             inputs,
         } => {
             let trace_name: Option<CString> =
@@ -140,7 +159,16 @@ fn do_main() -> Result<(), Box<dyn std::error::Error>> {
                 &inputs,
             )?;
 
-            let ctf_iter = CtfIterator::new(LoggingLevel::Warn, &params)?;
+            let mut ctf_iter = if begin.is_some() || end.is_some() {
+                let trimmer_params = UtilsPluginFilterTrimmerInitParams::new(
+                    begin.map(TrimmerBound::Time),
+                    end.map(TrimmerBound::Time),
+                    None,
+                )?;
+                CtfIterator::new_trimmed(LoggingLevel::Warn, &params, &trimmer_params)?
+            } else {
+                CtfIterator::new(LoggingLevel::Warn, &params)?
+            };
 
             println!("------------------------------------------------------------");
             println!("Trace Properties");
@@ -157,13 +185,15 @@ fn do_main() -> Result<(), Box<dyn std::error::Error>> {
             println!();
 
             if !no_events {
-                for event in ctf_iter {
+                while let Some(item) = ctf_iter.next_item() {
                     if running.load(Ordering::SeqCst) != 0 {
                         break;
                     }
 
-                    let event = event?;
-                    println!("{}", event);
+                    match item? {
+                        CtfItem::Event(event) => println!("{}", event),
+                        CtfItem::Discarded(discarded) => println!("{:?}", discarded),
+                    }
                 }
             }
         }
@@ -216,78 +246,75 @@ fn do_main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
 
-                for event in ctf_stream.events_chunk() {
+                for item in ctf_stream.items_chunk() {
                     if running.load(Ordering::SeqCst) != 0 {
                         break;
                     }
 
-                    println!("{}", event);
+                    match item {
+                        CtfItem::Event(event) => println!("{}", event),
+                        CtfItem::Discarded(discarded) => println!("{:?}", discarded),
+                    }
                 }
             }
         }
+        Cmd::ListPlugins { plugin_dirs } => {
+            let mut plugin_sets = vec![PluginSet::find_all(PluginLocations::ALL, false)?];
+            for dir in plugin_dirs.iter() {
+                plugin_sets.push(PluginSet::load_from_dir(dir, true, false)?);
+            }
+            for plugin in plugin_sets.iter().flat_map(|set| set.plugins()) {
+                print_plugin(&plugin)?;
+            }
+        }
     }
 
     Ok(())
 }
 
-/// Plugin descriptor related data, pointers to this data
-/// will end up in special linker sections in the binary
-/// so libbabeltrace2 can discover it
-///
-/// TODO: figure out how to work around https://github.com/rust-lang/rust/issues/47384
-pub mod proxy_plugin_descriptors {
-    use babeltrace2_sys::ffi::*;
-    use babeltrace2_sys::proxy_plugin_descriptors::*;
-
-    #[used]
-    #[link_section = "__bt_plugin_descriptors"]
-    pub static PLUGIN_DESC_PTR: __bt_plugin_descriptor_ptr =
-        __bt_plugin_descriptor_ptr(&PLUGIN_DESC);
-
-    #[used]
-    #[link_section = "__bt_plugin_component_class_descriptors"]
-    pub static SINK_COMP_DESC_PTR: __bt_plugin_component_class_descriptor_ptr =
-        __bt_plugin_component_class_descriptor_ptr(&SINK_COMP_DESC);
-
-    #[used]
-    #[link_section = "__bt_plugin_component_class_descriptor_attributes"]
-    pub static SINK_COMP_CLASS_INIT_ATTR_PTR: __bt_plugin_component_class_descriptor_attribute_ptr =
-        __bt_plugin_component_class_descriptor_attribute_ptr(&SINK_COMP_CLASS_INIT_ATTR);
-
-    #[used]
-    #[link_section = "__bt_plugin_component_class_descriptor_attributes"]
-    pub static SINK_COMP_CLASS_FINI_ATTR_PTR: __bt_plugin_component_class_descriptor_attribute_ptr =
-        __bt_plugin_component_class_descriptor_attribute_ptr(&SINK_COMP_CLASS_FINI_ATTR);
-
-    #[used]
-    #[link_section = "__bt_plugin_component_class_descriptor_attributes"]
-    pub static SINK_COMP_CLASS_GRAPH_CONF_ATTR_PTR:
-        __bt_plugin_component_class_descriptor_attribute_ptr =
-        __bt_plugin_component_class_descriptor_attribute_ptr(&SINK_COMP_CLASS_GRAPH_CONF_ATTR);
-}
-
-pub mod utils_plugin_descriptors {
-    use babeltrace2_sys::ffi::*;
-
-    #[link(
-        name = "babeltrace-plugin-utils",
-        kind = "static",
-        modifiers = "+whole-archive"
-    )]
-    extern "C" {
-        pub static __bt_plugin_descriptor_auto_ptr: *const __bt_plugin_descriptor;
+fn print_plugin(plugin: &Plugin) -> Result<(), Box<dyn std::error::Error>> {
+    let unknown = || String::from("(unknown)");
+    println!("{}:", plugin.name()?);
+    println!(
+        "  Path: {}",
+        plugin.path()?.unwrap_or_else(|| "(static)".into())
+    );
+    println!(
+        "  Version: {}",
+        plugin
+            .version()?
+            .map(|v| v.to_string())
+            .unwrap_or_else(unknown)
+    );
+    println!("  Author: {}", plugin.author()?.unwrap_or_else(unknown));
+    println!("  License: {}", plugin.license()?.unwrap_or_else(unknown));
+    println!("  Component classes:");
+    let name = plugin.name()?;
+    for class in plugin.source_component_classes() {
+        print_component_class(&name, &class)?;
     }
+    for class in plugin.filter_component_classes() {
+        print_component_class(&name, &class)?;
+    }
+    for class in plugin.sink_component_classes() {
+        print_component_class(&name, &class)?;
+    }
+    Ok(())
 }
 
-pub mod ctf_plugin_descriptors {
-    use babeltrace2_sys::ffi::*;
-
-    #[link(
-        name = "babeltrace-plugin-ctf",
-        kind = "static",
-        modifiers = "+whole-archive"
-    )]
-    extern "C" {
-        pub static __bt_plugin_descriptor_auto_ptr: *const __bt_plugin_descriptor;
+fn print_component_class<T>(
+    plugin_name: &str,
+    class: &ComponentClass<'_, T>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    print!(
+        "    {}.{}.{}",
+        class.class_type(),
+        plugin_name,
+        class.name()?
+    );
+    match class.description()? {
+        Some(desc) => println!(": {}", desc),
+        None => println!(),
     }
+    Ok(())
 }
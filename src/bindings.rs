@@ -595,6 +595,129 @@ extern "C" {
 extern "C" {
     pub fn bt_logging_get_minimal_level() -> bt_logging_level::Type;
 }
+pub const BT_LOG_PUT_CTX: _bindgen_ty_1 = 1;
+pub const BT_LOG_PUT_TAG: _bindgen_ty_1 = 2;
+pub const BT_LOG_PUT_SRC: _bindgen_ty_1 = 4;
+pub const BT_LOG_PUT_MSG: _bindgen_ty_1 = 8;
+pub const BT_LOG_PUT_STD: _bindgen_ty_1 = 65535;
+pub type _bindgen_ty_1 = ::std::os::raw::c_uint;
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct bt_log_message {
+    pub lvl: ::std::os::raw::c_int,
+    pub tag: *const ::std::os::raw::c_char,
+    pub buf: *mut ::std::os::raw::c_char,
+    pub e: *mut ::std::os::raw::c_char,
+    pub p: *mut ::std::os::raw::c_char,
+    pub tag_b: *mut ::std::os::raw::c_char,
+    pub tag_e: *mut ::std::os::raw::c_char,
+    pub msg_b: *mut ::std::os::raw::c_char,
+}
+#[test]
+fn bindgen_test_layout_bt_log_message() {
+    const UNINIT: ::std::mem::MaybeUninit<bt_log_message> = ::std::mem::MaybeUninit::uninit();
+    let ptr = UNINIT.as_ptr();
+    assert_eq!(
+        ::std::mem::size_of::<bt_log_message>(),
+        64usize,
+        concat!("Size of: ", stringify!(bt_log_message))
+    );
+    assert_eq!(
+        ::std::mem::align_of::<bt_log_message>(),
+        8usize,
+        concat!("Alignment of ", stringify!(bt_log_message))
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).lvl) as usize - ptr as usize },
+        0usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(lvl)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).tag) as usize - ptr as usize },
+        8usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(tag)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).buf) as usize - ptr as usize },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(buf)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).e) as usize - ptr as usize },
+        24usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(e)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).p) as usize - ptr as usize },
+        32usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(p)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).tag_b) as usize - ptr as usize },
+        40usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(tag_b)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).tag_e) as usize - ptr as usize },
+        48usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(tag_e)
+        )
+    );
+    assert_eq!(
+        unsafe { ::std::ptr::addr_of!((*ptr).msg_b) as usize - ptr as usize },
+        56usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(bt_log_message),
+            "::",
+            stringify!(msg_b)
+        )
+    );
+}
+pub type bt_log_output_cb = ::std::option::Option<
+    unsafe extern "C" fn(msg: *const bt_log_message, arg: *mut ::std::os::raw::c_void),
+>;
+extern "C" {
+    pub fn bt_log_set_output_v(
+        mask: ::std::os::raw::c_uint,
+        arg: *mut ::std::os::raw::c_void,
+        callback: bt_log_output_cb,
+    );
+}
 pub mod bt_component_class_sink_consume_method_status {
     pub type Type = ::std::os::raw::c_int;
     pub const BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_OK: Type = 0;
@@ -1,5 +1,6 @@
-use crate::{ffi, util, BtResult, Error};
+use crate::{ffi, util, BtResult, BtResultExt, Error, SelfComponentSource};
 use std::convert::TryInto;
+use std::ffi::CString;
 use std::slice;
 use uuid::Uuid;
 
@@ -7,6 +8,7 @@ pub type ClockCycles = u64;
 pub type ClockNanoseconds = i64;
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClockClassProperties {
     pub frequency: u64,
     pub offset_seconds: i64,
@@ -63,6 +65,51 @@ impl ClockClassProperties {
     }
 }
 
+/// A clock class created by a source component, a reference is held on it
+pub struct ClockClass {
+    pub(crate) inner: *mut ffi::bt_clock_class,
+}
+
+impl ClockClass {
+    /// Create a clock class with the given properties, the UUID is only
+    /// used when the origin isn't the Unix epoch
+    pub fn new(
+        component: &mut SelfComponentSource,
+        properties: &ClockClassProperties,
+    ) -> BtResult<Self> {
+        let inner = unsafe { ffi::bt_clock_class_create(component.inner as *mut _) };
+        if inner.is_null() {
+            return Err(Error::Memory);
+        }
+        let class = ClockClass { inner };
+        let p = properties;
+        unsafe {
+            ffi::bt_clock_class_set_frequency(inner, p.frequency);
+            ffi::bt_clock_class_set_offset(inner, p.offset_seconds, p.offset_cycles);
+            ffi::bt_clock_class_set_precision(inner, p.precision);
+            ffi::bt_clock_class_set_origin_is_unix_epoch(inner, p.unix_epoch_origin as _);
+            if let Some(name) = &p.name {
+                let name = CString::new(name.as_str())?;
+                ffi::bt_clock_class_set_name(inner, name.as_ptr()).capi_result()?;
+            }
+            if let Some(desc) = &p.description {
+                let desc = CString::new(desc.as_str())?;
+                ffi::bt_clock_class_set_description(inner, desc.as_ptr()).capi_result()?;
+            }
+            if let Some(uuid) = &p.uuid {
+                ffi::bt_clock_class_set_uuid(inner, uuid.as_bytes().as_ptr());
+            }
+        }
+        Ok(class)
+    }
+}
+
+impl Drop for ClockClass {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_clock_class_put_ref(self.inner) };
+    }
+}
+
 /// A clock snapshot is a snapshot of the value of a stream clock
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct ClockSnapshot {
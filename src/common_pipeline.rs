@@ -1,26 +1,29 @@
 use crate::{
-    BoxedRawProxyPluginState, BtResult, ComponentClassFilter, ComponentClassSink,
-    ComponentClassSource, ComponentFilter, ComponentSink, ComponentSource, CtfPlugin,
-    CtfPluginSrcExt, Error, Graph, Logger, LoggingLevel, ProxyPlugin, UtilsPlugin,
+    BoxedRawProxyPluginState, BtResult, ComponentFilter, ComponentLogLevel, ComponentLoggingLevels,
+    ComponentSink, ComponentSource, CtfPlugin, CtfPluginSrcExt, Error, Graph, Logger,
+    PipelineLoggingLevels, ProxyPlugin, UtilsPlugin, UtilsPluginFilterTrimmerInitParams,
 };
 
 pub(crate) struct CommonPipeline {
     _utils_plugin: UtilsPlugin,
     _ctf_plugin: CtfPlugin,
     _proxy_plugin: ProxyPlugin,
-    _ctf_src_class: ComponentClassSource,
-    _muxer_filter_class: ComponentClassFilter,
-    _proxy_sink_class: ComponentClassSink,
-    _ctf_src: ComponentSource,
-    _muxer_filter: ComponentFilter,
-    _proxy_sink: ComponentSink,
+    ctf_src: ComponentSource,
+    muxer_filter: ComponentFilter,
+    trimmer_filter: Option<ComponentFilter>,
+    proxy_sink: ComponentSink,
     pub(crate) graph: Graph,
     pub(crate) proxy_state: BoxedRawProxyPluginState,
 }
 
 impl CommonPipeline {
-    pub(crate) fn new<P: CtfPluginSrcExt>(log_level: LoggingLevel, params: &P) -> BtResult<Self> {
-        Logger::set_level(log_level);
+    pub(crate) fn new<P: CtfPluginSrcExt>(
+        log_levels: PipelineLoggingLevels,
+        params: &P,
+        trimmer_params: Option<&UtilsPluginFilterTrimmerInitParams>,
+    ) -> BtResult<Self> {
+        let global = log_levels.global;
+        Logger::set_level(global);
 
         // Load builtin plugins we need
         let utils_plugin = UtilsPlugin::load()?;
@@ -33,6 +36,9 @@ impl CommonPipeline {
         let ctf_src_class = ctf_plugin
             .borrow_source_component_class_by_name(params.source_component_class_name())?;
         let muxer_filter_class = utils_plugin.borrow_muxer_filter_component_class()?;
+        let trimmer_filter_class = trimmer_params
+            .map(|_| utils_plugin.borrow_trimmer_filter_component_class())
+            .transpose()?;
         let proxy_sink_class = proxy_plugin.borrow_output_sink_component_class_by_name()?;
 
         let mut graph = Graph::new()?;
@@ -41,22 +47,35 @@ impl CommonPipeline {
         let ctf_src = graph.add_source_component(
             &ctf_src_class,
             CtfPlugin::graph_node_name(),
-            params.parameters(),
-            log_level,
+            Some(params.parameters()),
+            params.log_level().unwrap_or(global),
         )?;
 
         let muxer_filter = graph.add_filter_component(
             &muxer_filter_class,
             UtilsPlugin::graph_node_name(),
-            log_level,
+            None,
+            log_levels.muxer.unwrap_or(global),
         )?;
 
+        let trimmer_filter = match (&trimmer_filter_class, trimmer_params) {
+            (Some(class), Some(trimmer_params)) => graph
+                .add_filter_component(
+                    class,
+                    UtilsPlugin::trimmer_graph_node_name(),
+                    Some(trimmer_params.params()),
+                    trimmer_params.log_level().unwrap_or(global),
+                )?
+                .into(),
+            _ => None,
+        };
+
         let mut proxy_state = BoxedRawProxyPluginState::new();
         let proxy_sink = graph.add_sink_component_with_initialize_method_data(
             &proxy_sink_class,
             ProxyPlugin::graph_node_name(),
             proxy_state.as_raw() as _,
-            log_level,
+            log_levels.proxy_sink.unwrap_or(global),
         )?;
 
         // Connect all available source output ports to the muxer filter input ports
@@ -76,24 +95,40 @@ impl CommonPipeline {
             graph.connect_ports(&out_port, &in_port)?;
         }
 
-        // Connect the mux'd filter output port to the proxy sink input port
-        log::debug!("Connecting muxer port to proxy sink");
+        // Connect the mux'd filter output port to the proxy sink input port,
+        // through the trimmer when there is one
         let in_port = proxy_sink.borrow_input_port_by_index(0)?;
         let out_port = muxer_filter.borrow_output_port_by_index(0)?;
-        graph.connect_ports(&out_port, &in_port)?;
+        if let Some(trimmer_filter) = &trimmer_filter {
+            log::debug!("Connecting muxer port to trimmer and trimmer port to proxy sink");
+            let trimmer_in_port = trimmer_filter.borrow_input_port_by_index(0)?;
+            let trimmer_out_port = trimmer_filter.borrow_output_port_by_index(0)?;
+            graph.connect_ports(&out_port, &trimmer_in_port)?;
+            graph.connect_ports(&trimmer_out_port, &in_port)?;
+        } else {
+            log::debug!("Connecting muxer port to proxy sink");
+            graph.connect_ports(&out_port, &in_port)?;
+        }
 
         Ok(CommonPipeline {
             _utils_plugin: utils_plugin,
             _ctf_plugin: ctf_plugin,
             _proxy_plugin: proxy_plugin,
-            _ctf_src_class: ctf_src_class,
-            _muxer_filter_class: muxer_filter_class,
-            _proxy_sink_class: proxy_sink_class,
-            _ctf_src: ctf_src,
-            _muxer_filter: muxer_filter,
-            _proxy_sink: proxy_sink,
+            ctf_src,
+            muxer_filter,
+            trimmer_filter,
+            proxy_sink,
             graph,
             proxy_state,
         })
     }
+
+    pub(crate) fn component_logging_levels(&self) -> ComponentLoggingLevels {
+        ComponentLoggingLevels {
+            source: self.ctf_src.get_logging_level(),
+            muxer: self.muxer_filter.get_logging_level(),
+            trimmer: self.trimmer_filter.as_ref().map(|t| t.get_logging_level()),
+            proxy_sink: self.proxy_sink.get_logging_level(),
+        }
+    }
 }
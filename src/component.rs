@@ -1,4 +1,4 @@
-use crate::{ffi, BtResult, Error, InputPort, OutputPort};
+use crate::{ffi, BtResult, Error, InputPort, LoggingLevel, OutputPort};
 use std::ffi::CStr;
 
 pub type ComponentSource = Component<ffi::bt_component_source>;
@@ -16,6 +16,12 @@ impl<T> Component<T> {
     pub fn in_port_name() -> &'static CStr {
         unsafe { CStr::from_bytes_with_nul_unchecked(Self::IN_PORT_NAME) }
     }
+
+    pub fn get_logging_level(&self) -> LoggingLevel {
+        // bt_component_{source,filter,sink}_as_component_const are casts
+        unsafe { ffi::bt_component_get_logging_level(self.inner as *const ffi::bt_component) }
+            .into()
+    }
 }
 
 impl ComponentSource {
@@ -33,9 +39,28 @@ impl ComponentSource {
             Ok(OutputPort { inner: port })
         }
     }
+
+    pub fn borrow_output_port_by_name(&self, name: &CStr) -> BtResult<OutputPort> {
+        let port = unsafe {
+            ffi::bt_component_source_borrow_output_port_by_name_const(self.inner, name.as_ptr())
+        };
+        if port.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(OutputPort { inner: port })
+        }
+    }
 }
 
 impl ComponentFilter {
+    pub fn get_input_port_count(&self) -> u64 {
+        unsafe { ffi::bt_component_filter_get_input_port_count(self.inner) }
+    }
+
+    pub fn get_output_port_count(&self) -> u64 {
+        unsafe { ffi::bt_component_filter_get_output_port_count(self.inner) }
+    }
+
     pub fn borrow_input_port_by_index(&self, index: u64) -> BtResult<InputPort> {
         let port =
             unsafe { ffi::bt_component_filter_borrow_input_port_by_index_const(self.inner, index) };
@@ -56,6 +81,28 @@ impl ComponentFilter {
             Ok(OutputPort { inner: port })
         }
     }
+
+    pub fn borrow_input_port_by_name(&self, name: &CStr) -> BtResult<InputPort> {
+        let port = unsafe {
+            ffi::bt_component_filter_borrow_input_port_by_name_const(self.inner, name.as_ptr())
+        };
+        if port.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(InputPort { inner: port })
+        }
+    }
+
+    pub fn borrow_output_port_by_name(&self, name: &CStr) -> BtResult<OutputPort> {
+        let port = unsafe {
+            ffi::bt_component_filter_borrow_output_port_by_name_const(self.inner, name.as_ptr())
+        };
+        if port.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(OutputPort { inner: port })
+        }
+    }
 }
 
 impl ComponentSink {
@@ -72,6 +119,17 @@ impl ComponentSink {
             Ok(InputPort { inner: port })
         }
     }
+
+    pub fn borrow_input_port_by_name(&self, name: &CStr) -> BtResult<InputPort> {
+        let port = unsafe {
+            ffi::bt_component_sink_borrow_input_port_by_name_const(self.inner, name.as_ptr())
+        };
+        if port.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(InputPort { inner: port })
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,10 +1,117 @@
-use crate::ffi;
+use crate::util::opt_owned_cstr;
+use crate::{ffi, BtResult, Plugin};
+use std::fmt;
+use std::marker::PhantomData;
 
-pub type ComponentClassSource = ComponentClass<ffi::bt_component_class_source>;
-pub type ComponentClassFilter = ComponentClass<ffi::bt_component_class_filter>;
-pub type ComponentClassSink = ComponentClass<ffi::bt_component_class_sink>;
+pub type ComponentClassSource<'a> = ComponentClass<'a, ffi::bt_component_class_source>;
+pub type ComponentClassFilter<'a> = ComponentClass<'a, ffi::bt_component_class_filter>;
+pub type ComponentClassSink<'a> = ComponentClass<'a, ffi::bt_component_class_sink>;
 
-/// An immutably borrowed component class
-pub struct ComponentClass<T> {
+/// A component class immutably borrowed from its plugin
+///
+/// ```compile_fail
+/// # use babeltrace2_sys::CtfPlugin;
+/// let class = {
+///     let plugin = CtfPlugin::load().unwrap();
+///     plugin.borrow_source_component_class_by_name(CtfPlugin::fs_name()).unwrap()
+/// };
+/// ```
+pub struct ComponentClass<'a, T> {
     pub(crate) inner: *const T,
+    _plugin: PhantomData<&'a Plugin>,
+}
+
+impl<'a, T> ComponentClass<'a, T> {
+    /// `inner` is owned by `plugin`
+    pub(crate) fn from_raw(_plugin: &'a Plugin, inner: *const T) -> Self {
+        ComponentClass {
+            inner,
+            _plugin: PhantomData,
+        }
+    }
+
+    fn as_component_class(&self) -> *const ffi::bt_component_class {
+        // bt_component_class_{source,filter,sink}_as_component_class_const are casts
+        self.inner as *const ffi::bt_component_class
+    }
+
+    pub fn class_type(&self) -> ComponentClassType {
+        ComponentClassType::from_raw(unsafe {
+            ffi::bt_component_class_get_type(self.as_component_class())
+        })
+    }
+
+    pub fn name(&self) -> BtResult<String> {
+        let name = unsafe { ffi::bt_component_class_get_name(self.as_component_class()) };
+        Ok(opt_owned_cstr(name)?.unwrap_or_default())
+    }
+
+    pub fn description(&self) -> BtResult<Option<String>> {
+        opt_owned_cstr(unsafe {
+            ffi::bt_component_class_get_description(self.as_component_class())
+        })
+    }
+
+    pub fn help(&self) -> BtResult<Option<String>> {
+        opt_owned_cstr(unsafe { ffi::bt_component_class_get_help(self.as_component_class()) })
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ComponentClassType {
+    Source,
+    Filter,
+    Sink,
+}
+
+impl ComponentClassType {
+    pub(crate) fn from_raw(t: ffi::bt_component_class_type::Type) -> Self {
+        use ffi::bt_component_class_type::*;
+        match t {
+            BT_COMPONENT_CLASS_TYPE_SOURCE => ComponentClassType::Source,
+            BT_COMPONENT_CLASS_TYPE_FILTER => ComponentClassType::Filter,
+            _ => ComponentClassType::Sink,
+        }
+    }
+}
+
+impl fmt::Display for ComponentClassType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentClassType::Source => f.write_str("source"),
+            ComponentClassType::Filter => f.write_str("filter"),
+            ComponentClassType::Sink => f.write_str("sink"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_type_from_raw() {
+        use ffi::bt_component_class_type::*;
+        let types = [
+            (
+                BT_COMPONENT_CLASS_TYPE_SOURCE,
+                ComponentClassType::Source,
+                "source",
+            ),
+            (
+                BT_COMPONENT_CLASS_TYPE_FILTER,
+                ComponentClassType::Filter,
+                "filter",
+            ),
+            (
+                BT_COMPONENT_CLASS_TYPE_SINK,
+                ComponentClassType::Sink,
+                "sink",
+            ),
+        ];
+        for (raw, typ, s) in types.iter() {
+            assert_eq!(ComponentClassType::from_raw(*raw), *typ);
+            assert_eq!(typ.to_string(), *s);
+        }
+    }
 }
@@ -0,0 +1,70 @@
+use crate::{
+    BtResult, ComponentLogLevel, CtfPluginSinkFsInitParams, CtfPluginSourceFsInitParams, Error,
+    Pipeline, PipelineBuilder, PipelineComponent, PipelineLoggingLevels, RunStatus,
+    UtilsPluginFilterTrimmerInitParams,
+};
+
+/// Reads CTF traces from disk and writes them back out as CTF with sink.ctf.fs,
+/// optionally trimmed.
+///
+/// source.ctf.fs -> filter.utils.muxer -> [filter.utils.trimmer] -> sink.ctf.fs
+pub struct CtfFsWriter {
+    pipeline: Pipeline,
+}
+
+impl CtfFsWriter {
+    const SOURCE_NAME: &'static str = "source.ctf.fs";
+    const MUXER_NAME: &'static str = "filter.utils.muxer";
+    const TRIMMER_NAME: &'static str = "filter.utils.trimmer";
+    const SINK_NAME: &'static str = "sink.ctf.fs";
+
+    pub fn new(
+        log_levels: impl Into<PipelineLoggingLevels>,
+        source_params: &CtfPluginSourceFsInitParams,
+        trimmer_params: Option<&UtilsPluginFilterTrimmerInitParams>,
+        sink_params: &CtfPluginSinkFsInitParams,
+    ) -> BtResult<Self> {
+        let log_levels = log_levels.into();
+        let mut builder = PipelineBuilder::new(log_levels.global)?;
+        builder
+            .set_component_log_level(source_params.log_level())
+            .add_source("ctf", "fs", Self::SOURCE_NAME, Some(source_params.params()))?
+            .set_component_log_level(log_levels.muxer)
+            .add_filter("utils", "muxer", Self::MUXER_NAME, None)?
+            .set_component_log_level(sink_params.log_level())
+            .add_sink("ctf", "fs", Self::SINK_NAME, Some(sink_params.params()))?;
+        if let Some(PipelineComponent::Source(src)) = builder.component(Self::SOURCE_NAME) {
+            if src.get_output_port_count() == 0 {
+                log::debug!("Input path doesn't appear to contain any stream data");
+                return Err(Error::CtfSourceMissingOutputPorts);
+            }
+        }
+        builder.connect_all(Self::SOURCE_NAME, Self::MUXER_NAME)?;
+        if let Some(trimmer_params) = trimmer_params {
+            builder
+                .set_component_log_level(trimmer_params.log_level())
+                .add_filter(
+                    "utils",
+                    "trimmer",
+                    Self::TRIMMER_NAME,
+                    Some(trimmer_params.params()),
+                )?
+                .connect(Self::MUXER_NAME, 0, Self::TRIMMER_NAME, 0)?
+                .connect(Self::TRIMMER_NAME, 0, Self::SINK_NAME, 0)?;
+        } else {
+            builder.connect(Self::MUXER_NAME, 0, Self::SINK_NAME, 0)?;
+        }
+        Ok(CtfFsWriter {
+            pipeline: builder.build(),
+        })
+    }
+
+    pub fn run_once(&mut self) -> BtResult<RunStatus> {
+        self.pipeline.run_once()
+    }
+
+    /// Write all of the traces
+    pub fn run(&mut self) -> BtResult<()> {
+        self.pipeline.run()
+    }
+}
@@ -1,61 +1,268 @@
 use crate::common_pipeline::CommonPipeline;
+use crate::proxy_plugin::QueuedItem;
 use crate::{
-    BtResult, CtfPluginSourceFsInitParams, LoggingLevel, OwnedEvent, RunStatus, StreamProperties,
-    TraceProperties,
+    BtResult, ClockNanoseconds, ComponentLoggingLevels, CtfPluginSourceFsInitParams, Discarded,
+    Error, EventRef, Message, OwnedEvent, PipelineLoggingLevels, RunStatus, StreamClassId,
+    StreamClassProperties, StreamProperties, TraceIndex, TraceProperties,
+    UtilsPluginFilterTrimmerInitParams,
 };
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An event, or a notice that events or packets were discarded, in trace order
+// Events are the common case, not worth boxing
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum CtfItem {
+    Event(OwnedEvent),
+    Discarded(Discarded),
+}
+
+/// Like `CtfItem`, with the event borrowed in place, see `CtfIterator::next_item_ref`
+pub enum CtfItemRef<'a> {
+    Event(EventRef<'a>),
+    Discarded(Discarded),
+}
 
 pub struct CtfIterator {
+    // NOTE: must be dropped before the graph that produced the message
+    current_event_message: Option<Message>,
     pipeline: CommonPipeline,
     last_run_status: RunStatus,
+    event_refs_error_returned: bool,
 }
 
 impl CtfIterator {
-    pub fn new(log_level: LoggingLevel, params: &CtfPluginSourceFsInitParams) -> BtResult<Self> {
-        let mut pipeline = CommonPipeline::new(log_level, params)?;
+    pub fn new(
+        log_levels: impl Into<PipelineLoggingLevels>,
+        params: &CtfPluginSourceFsInitParams,
+    ) -> BtResult<Self> {
+        Self::new_inner(log_levels.into(), params, None, false)
+    }
+
+    /// Like `new`, but only yields events within the trimmer's time range.
+    /// Events outside of the range are dropped before they're converted.
+    pub fn new_trimmed(
+        log_levels: impl Into<PipelineLoggingLevels>,
+        params: &CtfPluginSourceFsInitParams,
+        trimmer_params: &UtilsPluginFilterTrimmerInitParams,
+    ) -> BtResult<Self> {
+        Self::new_inner(log_levels.into(), params, Some(trimmer_params), false)
+    }
+
+    /// Like `new`, but events are kept in place rather than converted into `OwnedEvent`s.
+    /// Use `next_event_ref` to borrow them, `Iterator::next` still converts on demand.
+    pub fn new_with_event_refs(
+        log_levels: impl Into<PipelineLoggingLevels>,
+        params: &CtfPluginSourceFsInitParams,
+    ) -> BtResult<Self> {
+        Self::new_inner(log_levels.into(), params, None, true)
+    }
+
+    fn new_inner(
+        log_levels: PipelineLoggingLevels,
+        params: &CtfPluginSourceFsInitParams,
+        trimmer_params: Option<&UtilsPluginFilterTrimmerInitParams>,
+        retain_event_messages: bool,
+    ) -> BtResult<Self> {
+        let mut pipeline = CommonPipeline::new(log_levels, params, trimmer_params)?;
+        pipeline.proxy_state.as_mut().retain_event_messages = retain_event_messages;
+        pipeline.proxy_state.as_mut().seekable = true;
 
         // Do an initial run of the graph to connect and initialize all the components.
         // We'll have trace/stream metadata properties loaded and possibly some
         // events afterwards
-        let last_run_status = pipeline.graph.run_once()?;
+        let last_run_status = run_graph_once(&mut pipeline)?;
 
         Ok(CtfIterator {
+            current_event_message: None,
             pipeline,
             last_run_status,
+            event_refs_error_returned: false,
         })
     }
 
-    pub fn trace_properties(&self) -> &TraceProperties {
+    /// Properties of each trace, indexed by `TraceIndex`
+    pub fn trace_properties(&self) -> &[TraceProperties] {
         &self.pipeline.proxy_state.as_ref().trace_properties
     }
 
+    /// The logging level each component of the pipeline was created with
+    pub fn component_logging_levels(&self) -> ComponentLoggingLevels {
+        self.pipeline.component_logging_levels()
+    }
+
     pub fn stream_properties(&self) -> &BTreeSet<StreamProperties> {
         &self.pipeline.proxy_state.as_ref().stream_properties
     }
 
-    pub fn events_mut(&mut self) -> &mut VecDeque<OwnedEvent> {
-        &mut self.pipeline.proxy_state.as_mut().events
+    /// The stream classes, and their event classes, of each trace,
+    /// keyed by trace index and stream class ID
+    pub fn stream_classes(&self) -> &BTreeMap<(TraceIndex, StreamClassId), StreamClassProperties> {
+        &self.pipeline.proxy_state.as_ref().stream_classes
+    }
+
+    /// Returns the next event or discarded events/packets notice, in trace order.
+    ///
+    /// `Iterator::next` only yields the events.
+    pub fn next_item(&mut self) -> Option<BtResult<CtfItem>> {
+        let item = match self.pop_item()? {
+            Ok(item) => item,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(match item {
+            QueuedItem::Event(event) => Ok(CtfItem::Event(event)),
+            QueuedItem::EventMessage(msg, trace_index) => msg
+                .borrow_event_ref()
+                .and_then(|e| e.with_trace_index(trace_index).to_owned())
+                .map(CtfItem::Event),
+            QueuedItem::Discarded(discarded) => Ok(CtfItem::Discarded(discarded)),
+        })
+    }
+
+    /// Like `next_item`, but the event is borrowed without copying it, see `next_event_ref`
+    pub fn next_item_ref(&mut self) -> Option<BtResult<CtfItemRef<'_>>> {
+        if let Err(e) = self.check_event_refs()? {
+            return Some(Err(e));
+        }
+
+        // Release the previously borrowed event
+        let _ = self.current_event_message.take();
+
+        match self.pop_item()? {
+            Ok(QueuedItem::EventMessage(msg, trace_index)) => Some(
+                self.current_event_message
+                    .insert(msg)
+                    .borrow_event_ref()
+                    .map(|e| CtfItemRef::Event(e.with_trace_index(trace_index))),
+            ),
+            Ok(QueuedItem::Discarded(discarded)) => Some(Ok(CtfItemRef::Discarded(discarded))),
+            Ok(QueuedItem::Event(_)) => Some(Err(Error::EventRefsNotEnabled)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Borrow the next event without copying it, the event and its fields
+    /// are valid until the next call. Discarded notices are skipped, use
+    /// `next_item_ref` to observe them.
+    ///
+    /// Requires the iterator to be created with `new_with_event_refs`, otherwise
+    /// `Error::EventRefsNotEnabled` is returned once, followed by `None`.
+    pub fn next_event_ref(&mut self) -> Option<BtResult<EventRef<'_>>> {
+        if let Err(e) = self.check_event_refs()? {
+            return Some(Err(e));
+        }
+
+        // Release the previously borrowed event
+        let _ = self.current_event_message.take();
+
+        loop {
+            match self.pop_item()? {
+                Ok(QueuedItem::EventMessage(msg, trace_index)) => {
+                    return Some(
+                        self.current_event_message
+                            .insert(msg)
+                            .borrow_event_ref()
+                            .map(|e| e.with_trace_index(trace_index)),
+                    )
+                }
+                Ok(QueuedItem::Discarded(_)) => (),
+                Ok(QueuedItem::Event(_)) => return Some(Err(Error::EventRefsNotEnabled)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Returns None once the `EventRefsNotEnabled` error was returned
+    fn check_event_refs(&mut self) -> Option<BtResult<()>> {
+        if self.pipeline.proxy_state.as_ref().retain_event_messages {
+            Some(Ok(()))
+        } else if self.event_refs_error_returned {
+            None
+        } else {
+            self.event_refs_error_returned = true;
+            Some(Err(Error::EventRefsNotEnabled))
+        }
+    }
+
+    /// Pop the next queued item, running the graph until there is one
+    fn pop_item(&mut self) -> Option<BtResult<QueuedItem>> {
+        loop {
+            if let Some(item) = self.pipeline.proxy_state.as_mut().items.pop_front() {
+                return Some(Ok(item));
+            }
+
+            // Get another batch from upstream source component if not done
+            match self.run_once() {
+                Ok(RunStatus::End) => return None,
+                Ok(_) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Restart the iteration from the beginning of the trace, also once it ended
+    pub fn seek_beginning(&mut self) -> BtResult<()> {
+        let _ = self.current_event_message.take();
+        self.pipeline
+            .proxy_state
+            .as_mut()
+            .seek_beginning()
+            .map_err(Error::with_current_thread_causes)?;
+        self.reset_after_seek();
+        Ok(())
+    }
+
+    /// Continue the iteration from the first event at or after the given time.
+    ///
+    /// Returns `Error::SeekUnsupported` when the upstream components can't seek,
+    /// e.g. when trimming.
+    pub fn seek_ns_from_origin(&mut self, ns_from_origin: ClockNanoseconds) -> BtResult<()> {
+        let _ = self.current_event_message.take();
+        self.pipeline
+            .proxy_state
+            .as_mut()
+            .seek_ns_from_origin(ns_from_origin)
+            .map_err(Error::with_current_thread_causes)?;
+        self.reset_after_seek();
+        Ok(())
+    }
+
+    fn reset_after_seek(&mut self) {
+        self.last_run_status = RunStatus::Ok;
+    }
+
+    fn run_once(&mut self) -> BtResult<RunStatus> {
+        match self.last_run_status {
+            RunStatus::Ok | RunStatus::TryAgain => {
+                self.last_run_status = run_graph_once(&mut self.pipeline)?;
+                Ok(self.last_run_status)
+            }
+            RunStatus::End => Ok(RunStatus::End),
+        }
+    }
+}
+
+/// Run the graph once, the proxy sink doesn't end so that the iterator can seek,
+/// the run is over once the upstream iterator ended
+fn run_graph_once(pipeline: &mut CommonPipeline) -> BtResult<RunStatus> {
+    let status = pipeline.graph.run_once()?;
+    if pipeline.proxy_state.as_ref().upstream_ended {
+        Ok(RunStatus::End)
+    } else {
+        Ok(status)
     }
 }
 
 impl Iterator for CtfIterator {
     type Item = BtResult<OwnedEvent>;
 
+    /// Discarded notices are skipped, use `next_item` to observe them
     fn next(&mut self) -> Option<Self::Item> {
-        // Drain the previous message iterators batch of events
-        if let Some(event) = self.pipeline.proxy_state.as_mut().events.pop_front() {
-            Some(Ok(event))
-        } else {
-            // Get another batch from upstream source component if not done
-            match self.last_run_status {
-                RunStatus::Ok | RunStatus::TryAgain => match self.pipeline.graph.run_once() {
-                    Ok(last_run_status) => {
-                        self.last_run_status = last_run_status;
-                        self.events_mut().pop_front().map(Ok)
-                    }
-                    Err(e) => Some(Err(e)),
-                },
-                RunStatus::End => None,
+        loop {
+            match self.next_item()? {
+                Ok(CtfItem::Event(event)) => return Some(Ok(event)),
+                Ok(CtfItem::Discarded(_)) => (),
+                Err(e) => return Some(Err(e)),
             }
         }
     }
@@ -0,0 +1,88 @@
+use crate::common_pipeline::CommonPipeline;
+use crate::{
+    BtResult, CtfPluginSourceFsInitParams, OwnedMessage, PipelineLoggingLevels, RunStatus,
+    StreamClassId, StreamClassProperties, StreamProperties, TraceIndex, TraceProperties,
+    UtilsPluginFilterTrimmerInitParams,
+};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Like `CtfIterator`, but yields every message, not just events
+pub struct CtfMessageIterator {
+    pipeline: CommonPipeline,
+    last_run_status: RunStatus,
+}
+
+impl CtfMessageIterator {
+    pub fn new(
+        log_levels: impl Into<PipelineLoggingLevels>,
+        params: &CtfPluginSourceFsInitParams,
+    ) -> BtResult<Self> {
+        Self::new_inner(log_levels.into(), params, None)
+    }
+
+    /// Like `new`, but only yields messages within the trimmer's time range
+    pub fn new_trimmed(
+        log_levels: impl Into<PipelineLoggingLevels>,
+        params: &CtfPluginSourceFsInitParams,
+        trimmer_params: &UtilsPluginFilterTrimmerInitParams,
+    ) -> BtResult<Self> {
+        Self::new_inner(log_levels.into(), params, Some(trimmer_params))
+    }
+
+    fn new_inner(
+        log_levels: PipelineLoggingLevels,
+        params: &CtfPluginSourceFsInitParams,
+        trimmer_params: Option<&UtilsPluginFilterTrimmerInitParams>,
+    ) -> BtResult<Self> {
+        let mut pipeline = CommonPipeline::new(log_levels, params, trimmer_params)?;
+        pipeline.proxy_state.as_mut().collect_messages = true;
+
+        // Do an initial run of the graph to connect and initialize all the components
+        let last_run_status = pipeline.graph.run_once()?;
+
+        Ok(CtfMessageIterator {
+            pipeline,
+            last_run_status,
+        })
+    }
+
+    /// Properties of each trace, indexed by `TraceIndex`
+    pub fn trace_properties(&self) -> &[TraceProperties] {
+        &self.pipeline.proxy_state.as_ref().trace_properties
+    }
+
+    pub fn stream_properties(&self) -> &BTreeSet<StreamProperties> {
+        &self.pipeline.proxy_state.as_ref().stream_properties
+    }
+
+    /// The stream classes, and their event classes, of each trace,
+    /// keyed by trace index and stream class ID
+    pub fn stream_classes(&self) -> &BTreeMap<(TraceIndex, StreamClassId), StreamClassProperties> {
+        &self.pipeline.proxy_state.as_ref().stream_classes
+    }
+
+    fn messages_mut(&mut self) -> &mut VecDeque<OwnedMessage> {
+        &mut self.pipeline.proxy_state.as_mut().messages
+    }
+}
+
+impl Iterator for CtfMessageIterator {
+    type Item = BtResult<OwnedMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(msg) = self.messages_mut().pop_front() {
+                return Some(Ok(msg));
+            }
+
+            // Get another batch from upstream source component if not done
+            match self.last_run_status {
+                RunStatus::Ok | RunStatus::TryAgain => match self.pipeline.graph.run_once() {
+                    Ok(status) => self.last_run_status = status,
+                    Err(e) => return Some(Err(e)),
+                },
+                RunStatus::End => return None,
+            }
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use crate::{BtResult, CtfPlugin, CtfPluginSrcExt, Error, Value};
+use crate::{BtResult, ComponentLogLevel, CtfPlugin, CtfPluginSrcExt, Error, LoggingLevel, Value};
 use std::ffi::CStr;
 
 /// See <https://babeltrace.org/docs/v2.0/man7/babeltrace2-source.ctf.fs.7/#doc-_initialization_parameters>
 pub struct CtfPluginSourceFsInitParams {
     params: Value,
+    log_level: Option<LoggingLevel>,
     _inputs_val: Value,
     _trace_name_val: Option<Value>,
     _offset_ns_val: Option<Value>,
@@ -86,6 +87,7 @@ impl CtfPluginSourceFsInitParams {
 
         Ok(CtfPluginSourceFsInitParams {
             params,
+            log_level: None,
             _inputs_val: inputs_val,
             _trace_name_val: trace_name_val,
             _offset_ns_val: offset_ns_val,
@@ -131,6 +133,16 @@ impl CtfPluginSrcExt for CtfPluginSourceFsInitParams {
     }
 }
 
+impl ComponentLogLevel for CtfPluginSourceFsInitParams {
+    fn log_level(&self) -> Option<LoggingLevel> {
+        self.log_level
+    }
+
+    fn set_log_level(&mut self, log_level: Option<LoggingLevel>) {
+        self.log_level = log_level;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
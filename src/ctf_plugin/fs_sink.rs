@@ -0,0 +1,155 @@
+use crate::{BtResult, ComponentLogLevel, LoggingLevel, Value};
+use std::ffi::CStr;
+
+/// See <https://babeltrace.org/docs/v2.0/man7/babeltrace2-sink.ctf.fs.7/#doc-_initialization_parameters>
+pub struct CtfPluginSinkFsInitParams {
+    params: Value,
+    log_level: Option<LoggingLevel>,
+    _path_val: Value,
+    _assume_single_trace_val: Option<Value>,
+    _ignore_discarded_events_val: Option<Value>,
+    _ignore_discarded_packets_val: Option<Value>,
+    _quiet_val: Option<Value>,
+}
+
+impl CtfPluginSinkFsInitParams {
+    pub const PATH_KEY: &'static [u8] = b"path\0";
+    pub const ASSUME_SINGLE_TRACE_KEY: &'static [u8] = b"assume-single-trace\0";
+    pub const IGNORE_DISCARDED_EVENTS_KEY: &'static [u8] = b"ignore-discarded-events\0";
+    pub const IGNORE_DISCARDED_PACKETS_KEY: &'static [u8] = b"ignore-discarded-packets\0";
+    pub const QUIET_KEY: &'static [u8] = b"quiet\0";
+
+    pub fn new(
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-sink.ctf.fs.7/#doc-param-path
+        path: &CStr,
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-sink.ctf.fs.7/#doc-param-assume-single-trace
+        assume_single_trace: Option<bool>,
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-sink.ctf.fs.7/#doc-param-ignore-discarded-events
+        ignore_discarded_events: Option<bool>,
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-sink.ctf.fs.7/#doc-param-ignore-discarded-packets
+        ignore_discarded_packets: Option<bool>,
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-sink.ctf.fs.7/#doc-param-quiet
+        quiet: Option<bool>,
+    ) -> BtResult<Self> {
+        log::debug!(
+            "Creating sink.ctf.fs init params: path={:?}, assume-single-trace={:?}, ignore-discarded-events={:?}, ignore-discarded-packets={:?}, quiet={:?}",
+            path,
+            assume_single_trace,
+            ignore_discarded_events,
+            ignore_discarded_packets,
+            quiet,
+        );
+
+        let mut params = Value::new_map()?;
+
+        let path_val = Value::new_string_with(path)?;
+        params.insert_entry(Self::path_key(), &path_val)?;
+
+        let mut insert_bool = |key: &CStr, value: Option<bool>| -> BtResult<Option<Value>> {
+            if let Some(v) = value {
+                let val = Value::new_bool_with(v)?;
+                params.insert_entry(key, &val)?;
+                Ok(val.into())
+            } else {
+                Ok(None)
+            }
+        };
+        let assume_single_trace_val =
+            insert_bool(Self::assume_single_trace_key(), assume_single_trace)?;
+        let ignore_discarded_events_val =
+            insert_bool(Self::ignore_discarded_events_key(), ignore_discarded_events)?;
+        let ignore_discarded_packets_val = insert_bool(
+            Self::ignore_discarded_packets_key(),
+            ignore_discarded_packets,
+        )?;
+        let quiet_val = insert_bool(Self::quiet_key(), quiet)?;
+
+        Ok(CtfPluginSinkFsInitParams {
+            params,
+            log_level: None,
+            _path_val: path_val,
+            _assume_single_trace_val: assume_single_trace_val,
+            _ignore_discarded_events_val: ignore_discarded_events_val,
+            _ignore_discarded_packets_val: ignore_discarded_packets_val,
+            _quiet_val: quiet_val,
+        })
+    }
+
+    pub fn params(&self) -> &Value {
+        &self.params
+    }
+
+    fn path_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::PATH_KEY) }
+    }
+
+    fn assume_single_trace_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::ASSUME_SINGLE_TRACE_KEY) }
+    }
+
+    fn ignore_discarded_events_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::IGNORE_DISCARDED_EVENTS_KEY) }
+    }
+
+    fn ignore_discarded_packets_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::IGNORE_DISCARDED_PACKETS_KEY) }
+    }
+
+    fn quiet_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::QUIET_KEY) }
+    }
+}
+
+impl ComponentLogLevel for CtfPluginSinkFsInitParams {
+    fn log_level(&self) -> Option<LoggingLevel> {
+        self.log_level
+    }
+
+    fn set_log_level(&mut self, log_level: Option<LoggingLevel>) {
+        self.log_level = log_level;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cstrings_are_valid() {
+        assert_ne!(
+            CtfPluginSinkFsInitParams::path_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_ne!(
+            CtfPluginSinkFsInitParams::assume_single_trace_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_ne!(
+            CtfPluginSinkFsInitParams::ignore_discarded_events_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_ne!(
+            CtfPluginSinkFsInitParams::ignore_discarded_packets_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_ne!(
+            CtfPluginSinkFsInitParams::quiet_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+}
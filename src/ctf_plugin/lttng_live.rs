@@ -1,4 +1,4 @@
-use crate::{BtResult, CtfPlugin, CtfPluginSrcExt, Value};
+use crate::{BtResult, ComponentLogLevel, CtfPlugin, CtfPluginSrcExt, LoggingLevel, Value};
 use std::{ffi::CStr, fmt, str::FromStr};
 
 /// When the message iterator does not find the specified remote tracing
@@ -23,6 +23,7 @@ pub enum SessionNotFoundAction {
 /// See <https://babeltrace.org/docs/v2.0/man7/babeltrace2-source.ctf.lttng-live.7/#doc-_initialization_parameters>
 pub struct CtfPluginSourceLttnLiveInitParams {
     params: Value,
+    log_level: Option<LoggingLevel>,
     _inputs_val: Value,
     _session_not_found_action_val: Option<Value>,
 }
@@ -59,6 +60,7 @@ impl CtfPluginSourceLttnLiveInitParams {
 
         Ok(CtfPluginSourceLttnLiveInitParams {
             params,
+            log_level: None,
             _inputs_val: inputs_val,
             _session_not_found_action_val: session_not_found_action_val,
         })
@@ -87,6 +89,16 @@ impl CtfPluginSrcExt for CtfPluginSourceLttnLiveInitParams {
     }
 }
 
+impl ComponentLogLevel for CtfPluginSourceLttnLiveInitParams {
+    fn log_level(&self) -> Option<LoggingLevel> {
+        self.log_level
+    }
+
+    fn set_log_level(&mut self, log_level: Option<LoggingLevel>) {
+        self.log_level = log_level;
+    }
+}
+
 impl SessionNotFoundAction {
     fn to_cstr(self) -> &'static CStr {
         use SessionNotFoundAction::*;
@@ -1,12 +1,15 @@
-use crate::{BtResult, ComponentClassSource, Plugin, Value};
+use crate::{BtResult, ComponentClassSource, ComponentLogLevel, Plugin, Value};
 pub use fs::CtfPluginSourceFsInitParams;
+pub use fs_sink::CtfPluginSinkFsInitParams;
 pub use lttng_live::{CtfPluginSourceLttnLiveInitParams, SessionNotFoundAction};
 use std::ffi::CStr;
 
 mod fs;
+mod fs_sink;
 mod lttng_live;
 
 /// See <https://babeltrace.org/docs/v2.0/man7/babeltrace2-source.ctf.fs.7/>
+/// and <https://babeltrace.org/docs/v2.0/man7/babeltrace2-sink.ctf.fs.7/>
 pub struct CtfPlugin(Plugin);
 
 impl CtfPlugin {
@@ -23,7 +26,7 @@ impl CtfPlugin {
     pub fn borrow_source_component_class_by_name(
         &self,
         name: &CStr,
-    ) -> BtResult<ComponentClassSource> {
+    ) -> BtResult<ComponentClassSource<'_>> {
         self.0.borrow_source_component_class_by_name(name)
     }
 
@@ -44,7 +47,7 @@ impl CtfPlugin {
     }
 }
 
-pub(crate) trait CtfPluginSrcExt {
+pub(crate) trait CtfPluginSrcExt: ComponentLogLevel {
     fn parameters(&self) -> &Value;
     fn source_component_class_name(&self) -> &'static CStr;
 }
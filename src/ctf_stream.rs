@@ -1,9 +1,10 @@
 use crate::common_pipeline::CommonPipeline;
+use crate::proxy_plugin::QueuedItem;
 use crate::{
-    BtResult, CtfPluginSourceLttnLiveInitParams, LoggingLevel, OwnedEvent, RunStatus,
-    StreamProperties, TraceProperties,
+    BtResult, CtfItem, CtfPluginSourceLttnLiveInitParams, OwnedEvent, PipelineLoggingLevels,
+    RunStatus, StreamClassId, StreamClassProperties, StreamProperties, TraceIndex, TraceProperties,
 };
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet};
 
 pub struct CtfStream {
     pipeline: CommonPipeline,
@@ -12,10 +13,10 @@ pub struct CtfStream {
 
 impl CtfStream {
     pub fn new(
-        log_level: LoggingLevel,
+        log_levels: impl Into<PipelineLoggingLevels>,
         params: &CtfPluginSourceLttnLiveInitParams,
     ) -> BtResult<Self> {
-        let pipeline = CommonPipeline::new(log_level, params)?;
+        let pipeline = CommonPipeline::new(log_levels.into(), params, None)?;
         Ok(CtfStream {
             pipeline,
             metadata_recvd: false,
@@ -44,7 +45,8 @@ impl CtfStream {
         self.metadata_recvd
     }
 
-    pub fn trace_properties(&self) -> &TraceProperties {
+    /// Properties of each trace, indexed by `TraceIndex`
+    pub fn trace_properties(&self) -> &[TraceProperties] {
         &self.pipeline.proxy_state.as_ref().trace_properties
     }
 
@@ -52,13 +54,32 @@ impl CtfStream {
         &self.pipeline.proxy_state.as_ref().stream_properties
     }
 
-    pub fn events_mut(&mut self) -> &mut VecDeque<OwnedEvent> {
-        &mut self.pipeline.proxy_state.as_mut().events
+    /// The stream classes, and their event classes, of each trace,
+    /// keyed by trace index and stream class ID
+    pub fn stream_classes(&self) -> &BTreeMap<(TraceIndex, StreamClassId), StreamClassProperties> {
+        &self.pipeline.proxy_state.as_ref().stream_classes
     }
 
+    /// The events of the last update, discarded notices are skipped
     pub fn events_chunk(&mut self) -> CtfStreamChunkIterator {
         CtfStreamChunkIterator { stream: self }
     }
+
+    /// The events and discarded events/packets notices of the last update, in trace order
+    pub fn items_chunk(&mut self) -> CtfStreamItemChunkIterator<'_> {
+        CtfStreamItemChunkIterator { stream: self }
+    }
+
+    fn pop_item(&mut self) -> Option<CtfItem> {
+        loop {
+            match self.pipeline.proxy_state.as_mut().items.pop_front()? {
+                QueuedItem::Event(event) => return Some(CtfItem::Event(event)),
+                QueuedItem::Discarded(discarded) => return Some(CtfItem::Discarded(discarded)),
+                // Event messages are only retained by `CtfIterator::new_with_event_refs`
+                QueuedItem::EventMessage(..) => (),
+            }
+        }
+    }
 }
 
 pub struct CtfStreamChunkIterator<'a> {
@@ -70,6 +91,22 @@ impl<'a> Iterator for CtfStreamChunkIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         // Drain the previous message iterators batch of events
-        self.stream.events_mut().pop_front()
+        loop {
+            if let CtfItem::Event(event) = self.stream.pop_item()? {
+                return Some(event);
+            }
+        }
+    }
+}
+
+pub struct CtfStreamItemChunkIterator<'a> {
+    stream: &'a mut CtfStream,
+}
+
+impl<'a> Iterator for CtfStreamItemChunkIterator<'a> {
+    type Item = CtfItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stream.pop_item()
     }
 }
@@ -0,0 +1,24 @@
+use crate::{ClockNanoseconds, StreamId, TraceIndex};
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiscardedKind {
+    Events,
+    Packets,
+}
+
+/// The tracer, or a component, lost data in a stream
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Discarded {
+    pub kind: DiscardedKind,
+    /// Index of the trace the stream belongs to.
+    /// Assigned by the iterators, zero when converted on its own.
+    pub trace_index: TraceIndex,
+    pub stream_id: StreamId,
+    /// Number of discarded items, if known
+    pub count: Option<u64>,
+    /// Time range of the gap, if the stream class supports it
+    pub beginning_clock_snapshot: Option<ClockNanoseconds>,
+    pub end_clock_snapshot: Option<ClockNanoseconds>,
+}
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EnvValue {
     Integer(i64),
     String(String),
@@ -8,6 +9,8 @@ pub enum EnvValue {
 
 /// Trace environment key-value store
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Env {
     pub(crate) entries: BTreeMap<String, EnvValue>,
 }
@@ -1,3 +1,4 @@
+use crate::{ErrorCauses, MessageType};
 use thiserror::Error;
 
 pub type BtResult<T> = Result<T, Error>;
@@ -12,10 +13,18 @@ pub enum Error {
     ResourceBorrow,
     #[error("libbabeltrace encountered a memory error")]
     Memory,
+    #[error("libbabeltrace returned a try again status")]
+    TryAgain,
+    #[error("{0}. Caused by: {1}")]
+    WithCauses(Box<Error>, ErrorCauses),
     #[error("At least one CTF-containing input directory is required")]
     CtfSourceRequiresInputs,
     #[error("At least one CTF output port is required, check that the input path contains at least one stream")]
     CtfSourceMissingOutputPorts,
+    #[error("The trimmer requires at least one of the begin or end bounds")]
+    TrimmerRequiresBound,
+    #[error("Can't create the field class {0}, length and selector fields aren't supported")]
+    UnsupportedFieldClass(String),
     #[error("At least one sink input port is required")]
     ProxySinkMissingInputPort,
     #[error("Encountered a libbabeltrace string with invalid UTF-8")]
@@ -38,18 +47,118 @@ pub enum Error {
     CtfMetadataDecoderStatus(isize),
     #[error("libbabeltrace returned NULL when attempting to create a CTF message iterator")]
     CtfMessageIterCreate,
+    #[error("Borrowed events require an iterator created with event references enabled")]
+    EventRefsNotEnabled,
+    #[error("No field found at path '{0}'")]
+    FieldPathNotFound(String),
+    #[error("The field '{0}' is not {1}")]
+    FieldTypeMismatch(String, &'static str),
+    #[error("Unknown field scope '{0}', expected one of payload, specific_context, common_context or packet_context")]
+    FieldScope(String),
+    #[error("The upstream message iterator can't seek {0}")]
+    SeekUnsupported(String),
+    #[error("No plugin named '{0}' was found")]
+    PluginNotFound(String),
+    #[error("The static plugin '{0}' isn't registered, invoke `babeltrace2_sys::register_static_plugins!()` in the binary crate")]
+    StaticPluginNotRegistered(String),
+    #[error("No component class '{0}' was found")]
+    ComponentClassNotFound(String),
+    #[error("A component named '{0}' already exists in the pipeline")]
+    DuplicateComponentName(String),
+    #[error("No component named '{0}' in the pipeline")]
+    ComponentNotFound(String),
+    #[error("The {1} component '{0}' has no {2} port {3}")]
+    PortNotFound(String, &'static str, &'static str, String),
+    #[error("Packet clock snapshots require a stream class clock")]
+    PacketClockSnapshotsRequireClock,
+    #[error("Packet clock snapshots require a stream class that supports packets")]
+    PacketClockSnapshotsRequirePackets,
+    #[error("The stream class doesn't support packets")]
+    PacketsNotSupported,
+    #[error("The stream class supports packets, a packet is required")]
+    PacketRequired,
+    #[error("The stream class requires a default clock snapshot for this message")]
+    ClockSnapshotRequired,
+    #[error("The stream class has no default clock snapshot for this message")]
+    UnexpectedClockSnapshot,
+    #[error("Expected an event message, found a {0:?} message")]
+    NotAnEventMessage(MessageType),
+    #[error("A {0} component has no {1} ports")]
+    NoPortsOfKind(&'static str, &'static str),
+    #[error("The value is not a {0}")]
+    ValueTypeMismatch(&'static str),
+    #[error("The value is frozen and can no longer be modified")]
+    ValueFrozen,
+    #[error("A custom component method panicked: {0}")]
+    ComponentPanic(String),
+    #[error("Failed to connect {0} to {1}. {2}")]
+    PortConnection(String, String, Box<Error>),
+    #[cfg(feature = "serde")]
+    #[error("Failed to deserialize field. {0}")]
+    FieldDeserialize(String),
+    #[cfg(feature = "serde")]
+    #[error("Failed to deserialize field. Missing member '{1}' in '{0}'")]
+    FieldMissing(String, &'static str),
+}
+
+impl Error {
+    /// Take the current thread's libbabeltrace2 error causes, if any, and attach them
+    /// to this error.
+    ///
+    /// Only done at the top-level API boundary, e.g. `Graph::run_once`. Inside component
+    /// callbacks the error stays on the thread, see `append_to_current_thread`.
+    pub(crate) fn with_current_thread_causes(self) -> Self {
+        if let Error::WithCauses(..) = self {
+            return self;
+        }
+        match ErrorCauses::take_current_thread_error() {
+            Some(causes) if !causes.0.is_empty() => Error::WithCauses(Box::new(self), causes),
+            _ => self,
+        }
+    }
+
+    /// The error without its libbabeltrace2 error causes
+    pub fn kind(&self) -> &Error {
+        match self {
+            Error::WithCauses(e, _) => e,
+            e => e,
+        }
+    }
+
+    /// The libbabeltrace2 error causes, most recent first
+    pub fn causes(&self) -> Option<&ErrorCauses> {
+        match self {
+            Error::WithCauses(_, causes) => Some(causes),
+            _ => None,
+        }
+    }
+
+    /// Append this error as a cause of the current thread's error, before
+    /// returning an error status to libbabeltrace2
+    pub(crate) fn append_to_current_thread(&self, file_name: &str, line_number: u32) {
+        crate::error_cause::append_current_thread_error_cause(
+            file_name,
+            line_number,
+            &self.to_string(),
+        );
+    }
 }
 
 pub trait BtResultExt {
     fn capi_result(self) -> BtResult<()>;
 }
 
+// Status codes shared by the libbabeltrace2 functions, __BT_FUNC_STATUS_* in func-status.h
+const FUNC_STATUS_AGAIN: std::os::raw::c_int = 11;
+const FUNC_STATUS_MEMORY_ERROR: std::os::raw::c_int = -12;
+
 impl BtResultExt for std::os::raw::c_int {
     fn capi_result(self) -> BtResult<()> {
-        if self == 0 {
-            Ok(())
-        } else {
-            Err(Error::Failure(self as _))
+        match self {
+            0 => Ok(()),
+            FUNC_STATUS_AGAIN => Err(Error::TryAgain),
+            FUNC_STATUS_MEMORY_ERROR => Err(Error::Memory),
+            _ => Err(Error::Failure(self as _)),
         }
     }
 }
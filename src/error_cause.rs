@@ -0,0 +1,274 @@
+use crate::{ffi, ComponentClassType};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::raw::c_char;
+
+/// The causes of a libbabeltrace2 error, most recent first.
+///
+/// The last cause is the root cause of the error.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ErrorCauses(pub Vec<ErrorCause>);
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ErrorCause {
+    pub actor: ErrorCauseActor,
+    pub message: String,
+    pub module_name: String,
+    pub file_name: String,
+    pub line_number: u64,
+}
+
+/// The actor that appended an error cause
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ErrorCauseActor {
+    Unknown,
+    Component {
+        component_name: String,
+        class_type: ComponentClassType,
+        class_name: String,
+        plugin_name: Option<String>,
+    },
+    ComponentClass {
+        class_type: ComponentClassType,
+        class_name: String,
+        plugin_name: Option<String>,
+    },
+    MessageIterator {
+        component_name: String,
+        output_port_name: String,
+        class_type: ComponentClassType,
+        class_name: String,
+        plugin_name: Option<String>,
+    },
+}
+
+impl ErrorCauses {
+    /// Take the current thread's error, if any, clearing it
+    pub(crate) fn take_current_thread_error() -> Option<Self> {
+        let error = unsafe { ffi::bt_current_thread_take_error() };
+        if error.is_null() {
+            return None;
+        }
+        let count = unsafe { ffi::bt_error_get_cause_count(error) };
+        let causes = (0..count)
+            .rev()
+            .map(|index| {
+                let cause = unsafe { ffi::bt_error_borrow_cause_by_index(error, index) };
+                ErrorCause::from_raw(cause)
+            })
+            .collect();
+        unsafe { ffi::bt_error_release(error) };
+        Some(ErrorCauses(causes))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ErrorCause> {
+        self.0.iter()
+    }
+
+    pub fn root_cause(&self) -> Option<&ErrorCause> {
+        self.0.last()
+    }
+}
+
+impl ErrorCause {
+    fn from_raw(cause: *const ffi::bt_error_cause) -> Self {
+        use ffi::bt_error_cause_actor_type::*;
+        let actor = unsafe {
+            match ffi::bt_error_cause_get_actor_type(cause) {
+                BT_ERROR_CAUSE_ACTOR_TYPE_COMPONENT => ErrorCauseActor::Component {
+                    component_name: lossy_str(
+                        ffi::bt_error_cause_component_actor_get_component_name(cause),
+                    ),
+                    class_type: ComponentClassType::from_raw(
+                        ffi::bt_error_cause_component_actor_get_component_class_type(cause),
+                    ),
+                    class_name: lossy_str(
+                        ffi::bt_error_cause_component_actor_get_component_class_name(cause),
+                    ),
+                    plugin_name: opt_lossy_str(
+                        ffi::bt_error_cause_component_actor_get_plugin_name(cause),
+                    ),
+                },
+                BT_ERROR_CAUSE_ACTOR_TYPE_COMPONENT_CLASS => ErrorCauseActor::ComponentClass {
+                    class_type: ComponentClassType::from_raw(
+                        ffi::bt_error_cause_component_class_actor_get_component_class_type(cause),
+                    ),
+                    class_name: lossy_str(
+                        ffi::bt_error_cause_component_class_actor_get_component_class_name(cause),
+                    ),
+                    plugin_name: opt_lossy_str(
+                        ffi::bt_error_cause_component_class_actor_get_plugin_name(cause),
+                    ),
+                },
+                BT_ERROR_CAUSE_ACTOR_TYPE_MESSAGE_ITERATOR => ErrorCauseActor::MessageIterator {
+                    component_name: lossy_str(
+                        ffi::bt_error_cause_message_iterator_actor_get_component_name(cause),
+                    ),
+                    output_port_name: lossy_str(
+                        ffi::bt_error_cause_message_iterator_actor_get_component_output_port_name(
+                            cause,
+                        ),
+                    ),
+                    class_type: ComponentClassType::from_raw(
+                        ffi::bt_error_cause_message_iterator_actor_get_component_class_type(cause),
+                    ),
+                    class_name: lossy_str(
+                        ffi::bt_error_cause_message_iterator_actor_get_component_class_name(cause),
+                    ),
+                    plugin_name: opt_lossy_str(
+                        ffi::bt_error_cause_message_iterator_actor_get_plugin_name(cause),
+                    ),
+                },
+                _ => ErrorCauseActor::Unknown,
+            }
+        };
+        unsafe {
+            ErrorCause {
+                actor,
+                message: lossy_str(ffi::bt_error_cause_get_message(cause)),
+                module_name: lossy_str(ffi::bt_error_cause_get_module_name(cause)),
+                file_name: lossy_str(ffi::bt_error_cause_get_file_name(cause)),
+                line_number: ffi::bt_error_cause_get_line_number(cause),
+            }
+        }
+    }
+}
+
+/// Append a cause to the current thread's error, for Rust errors
+/// returned to libbabeltrace2 as an error status
+pub(crate) fn append_current_thread_error_cause(file_name: &str, line_number: u32, msg: &str) {
+    let module_name = b"babeltrace2-sys\0";
+    let fmt = b"%s\0";
+    let (file_name, msg) = match (CString::new(file_name), CString::new(msg)) {
+        (Ok(f), Ok(m)) => (f, m),
+        _ => return,
+    };
+    unsafe {
+        ffi::bt_current_thread_error_append_cause_from_unknown(
+            module_name.as_ptr() as *const c_char,
+            file_name.as_ptr(),
+            line_number as _,
+            fmt.as_ptr() as *const c_char,
+            msg.as_ptr(),
+        )
+    };
+}
+
+fn lossy_str(ptr: *const c_char) -> String {
+    opt_lossy_str(ptr).unwrap_or_default()
+}
+
+fn opt_lossy_str(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(
+            unsafe { CStr::from_ptr(ptr) }
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
+
+impl fmt::Display for ErrorCauses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, cause) in self.0.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(" Caused by: ")?;
+            }
+            write!(f, "{}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ErrorCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.actor {
+            ErrorCauseActor::Unknown => write!(f, "[{}]", self.module_name)?,
+            actor => write!(f, "[{}]", actor)?,
+        }
+        write!(
+            f,
+            " ({}:{}) {}",
+            self.file_name,
+            self.line_number,
+            self.message.trim_end()
+        )
+    }
+}
+
+impl fmt::Display for ErrorCauseActor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ErrorCauseActor::*;
+        let class = |f: &mut fmt::Formatter<'_>, t, p: &Option<String>, c| match p {
+            Some(p) => write!(f, "{}.{}.{}", t, p, c),
+            None => write!(f, "{}.{}", t, c),
+        };
+        match self {
+            Unknown => f.write_str("unknown"),
+            Component {
+                component_name,
+                class_type,
+                class_name,
+                plugin_name,
+            } => {
+                write!(f, "{} (", component_name)?;
+                class(f, class_type, plugin_name, class_name)?;
+                f.write_str(")")
+            }
+            ComponentClass {
+                class_type,
+                class_name,
+                plugin_name,
+            } => class(f, class_type, plugin_name, class_name),
+            MessageIterator {
+                component_name,
+                output_port_name,
+                class_type,
+                class_name,
+                plugin_name,
+            } => {
+                write!(f, "{} (", component_name)?;
+                class(f, class_type, plugin_name, class_name)?;
+                write!(f, "): {}", output_port_name)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_most_recent_first() {
+        let causes = ErrorCauses(vec![
+            ErrorCause {
+                actor: ErrorCauseActor::Component {
+                    component_name: "sink.proxy.output".to_owned(),
+                    class_type: ComponentClassType::Sink,
+                    class_name: "output".to_owned(),
+                    plugin_name: Some("proxy".to_owned()),
+                },
+                message: "Sink cannot consume\n".to_owned(),
+                module_name: "babeltrace2-sys".to_owned(),
+                file_name: "src/proxy_plugin.rs".to_owned(),
+                line_number: 10,
+            },
+            ErrorCause {
+                actor: ErrorCauseActor::Unknown,
+                message: "Failed to read stream file".to_owned(),
+                module_name: "ctf".to_owned(),
+                file_name: "fs.c".to_owned(),
+                line_number: 20,
+            },
+        ]);
+        assert_eq!(causes.root_cause().unwrap().module_name, "ctf");
+        assert_eq!(
+            causes.to_string(),
+            "[sink.proxy.output (sink.proxy.output)] (src/proxy_plugin.rs:10) Sink cannot consume \
+             Caused by: [ctf] (fs.c:20) Failed to read stream file"
+        );
+    }
+}
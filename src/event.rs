@@ -1,7 +1,9 @@
 use crate::{
-    ffi, util, BtResult, ClockNanoseconds, ClockSnapshot, Error, Field, OwnedField, StreamId,
+    ffi, util, BtResult, ClockNanoseconds, ClockSnapshot, Error, Field, FieldClass, FieldRef,
+    OwnedField, OwnedFieldClass, Stream, StreamId, Trace, TraceIndex, TraceProperties,
 };
 use std::fmt;
+use std::marker::PhantomData;
 
 pub struct Event {
     pub(crate) clock_snapshot: Option<ClockSnapshot>,
@@ -15,6 +17,7 @@ impl Event {
         let class_properties = self.class_properties()?;
         let properties = self.properties()?;
         Ok(OwnedEvent {
+            trace_index: 0,
             stream_id,
             clock_snapshot,
             class_properties,
@@ -33,29 +36,36 @@ impl Event {
         self.clock_snapshot.and_then(|c| c.ns_from_origin())
     }
 
-    pub fn class_properties(&self) -> BtResult<EventClassProperties> {
+    pub fn trace(&self) -> BtResult<Trace> {
+        let stream = unsafe { ffi::bt_event_borrow_stream_const(self.inner) };
+        if stream.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Stream { inner: stream }.trace()
+        }
+    }
+
+    pub fn class(&self) -> BtResult<EventClass> {
         let class = unsafe { ffi::bt_event_borrow_class_const(self.inner) };
         if class.is_null() {
-            return Err(Error::ResourceBorrow);
-        }
-        let id = unsafe { ffi::bt_event_class_get_id(class) };
-        let name_cstr = unsafe { ffi::bt_event_class_get_name(class) };
-        let name = util::opt_owned_cstr(name_cstr)?;
-        let mut log_level_raw = 0;
-        let log_level_avail =
-            unsafe { ffi::bt_event_class_get_log_level(class, &mut log_level_raw) };
-        let log_level = if log_level_avail
-            == ffi::bt_property_availability::BT_PROPERTY_AVAILABILITY_AVAILABLE
-        {
-            EventLogLevel::from_raw(log_level_raw)
+            Err(Error::ResourceBorrow)
         } else {
-            None
-        };
-        Ok(EventClassProperties {
-            id,
-            name,
-            log_level,
-        })
+            Ok(EventClass { inner: class })
+        }
+    }
+
+    pub fn class_properties(&self) -> BtResult<EventClassProperties> {
+        self.class()?.properties()
+    }
+
+    /// The field class of the event payload, if the event class has one
+    pub fn payload_class(&self) -> Option<FieldClass<'_>> {
+        payload_class(unsafe { ffi::bt_event_borrow_class_const(self.inner) })
+    }
+
+    /// The field class of the event specific context, if the event class has one
+    pub fn specific_context_class(&self) -> Option<FieldClass<'_>> {
+        specific_context_class(unsafe { ffi::bt_event_borrow_class_const(self.inner) })
     }
 
     pub fn properties(&self) -> BtResult<EventProperties> {
@@ -107,8 +117,180 @@ impl Event {
     }
 }
 
+pub struct EventClass {
+    pub(crate) inner: *const ffi::bt_event_class,
+}
+
+impl EventClass {
+    pub fn properties(&self) -> BtResult<EventClassProperties> {
+        let id = unsafe { ffi::bt_event_class_get_id(self.inner) };
+        let name_cstr = unsafe { ffi::bt_event_class_get_name(self.inner) };
+        let name = util::opt_owned_cstr(name_cstr)?;
+        let mut log_level_raw = 0;
+        let log_level_avail =
+            unsafe { ffi::bt_event_class_get_log_level(self.inner, &mut log_level_raw) };
+        let log_level = if log_level_avail
+            == ffi::bt_property_availability::BT_PROPERTY_AVAILABILITY_AVAILABLE
+        {
+            EventLogLevel::from_raw(log_level_raw)
+        } else {
+            None
+        };
+        Ok(EventClassProperties {
+            id,
+            name,
+            log_level,
+        })
+    }
+
+    /// Eclipse Modeling Framework URI of the event class
+    pub fn emf_uri(&self) -> BtResult<Option<String>> {
+        util::opt_owned_cstr(unsafe { ffi::bt_event_class_get_emf_uri(self.inner) })
+    }
+
+    pub fn payload_class(&self) -> Option<FieldClass<'_>> {
+        payload_class(self.inner)
+    }
+
+    pub fn specific_context_class(&self) -> Option<FieldClass<'_>> {
+        specific_context_class(self.inner)
+    }
+
+    pub fn schema(&self) -> BtResult<EventClassSchema> {
+        Ok(EventClassSchema {
+            properties: self.properties()?,
+            emf_uri: self.emf_uri()?,
+            specific_context: self
+                .specific_context_class()
+                .map(|c| c.to_owned())
+                .transpose()?,
+            payload: self.payload_class().map(|c| c.to_owned()).transpose()?,
+        })
+    }
+}
+
+fn payload_class<'a>(class: *const ffi::bt_event_class) -> Option<FieldClass<'a>> {
+    if class.is_null() {
+        None
+    } else {
+        FieldClass::from_raw(unsafe { ffi::bt_event_class_borrow_payload_field_class_const(class) })
+    }
+}
+
+fn specific_context_class<'a>(class: *const ffi::bt_event_class) -> Option<FieldClass<'a>> {
+    if class.is_null() {
+        None
+    } else {
+        FieldClass::from_raw(unsafe {
+            ffi::bt_event_class_borrow_specific_context_field_class_const(class)
+        })
+    }
+}
+
+/// Description of an event class, available without decoding any events
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventClassSchema {
+    pub properties: EventClassProperties,
+    pub emf_uri: Option<String>,
+    pub specific_context: Option<OwnedFieldClass>,
+    pub payload: Option<OwnedFieldClass>,
+}
+
+/// An event borrowed in place from the message that carries it,
+/// its fields are only copied when requested
+pub struct EventRef<'msg> {
+    inner: Event,
+    trace_index: TraceIndex,
+    _msg: PhantomData<&'msg ()>,
+}
+
+impl<'msg> EventRef<'msg> {
+    pub(crate) fn new(inner: Event) -> Self {
+        EventRef {
+            inner,
+            trace_index: 0,
+            _msg: PhantomData,
+        }
+    }
+
+    pub(crate) fn with_trace_index(mut self, trace_index: TraceIndex) -> Self {
+        self.trace_index = trace_index;
+        self
+    }
+
+    /// Index of the originating trace, see `CtfIterator::trace_properties`.
+    /// Assigned by the iterators, zero when borrowed on its own.
+    pub fn trace_index(&self) -> TraceIndex {
+        self.trace_index
+    }
+
+    pub fn to_owned(&self) -> BtResult<OwnedEvent> {
+        let mut event = Event {
+            clock_snapshot: self.inner.clock_snapshot,
+            inner: self.inner.inner,
+        }
+        .to_owned()?;
+        event.trace_index = self.trace_index;
+        Ok(event)
+    }
+
+    pub fn stream_id(&self) -> StreamId {
+        self.inner.stream_id()
+    }
+
+    pub fn clock_snapshot(&self) -> Option<ClockNanoseconds> {
+        self.inner.clock_snapshot()
+    }
+
+    pub fn trace_properties(&self) -> BtResult<TraceProperties> {
+        self.inner.trace()?.properties()
+    }
+
+    pub fn class_properties(&self) -> BtResult<EventClassProperties> {
+        self.inner.class_properties()
+    }
+
+    pub fn payload_class(&self) -> Option<FieldClass<'msg>> {
+        payload_class(unsafe { ffi::bt_event_borrow_class_const(self.inner.inner) })
+    }
+
+    pub fn specific_context_class(&self) -> Option<FieldClass<'msg>> {
+        specific_context_class(unsafe { ffi::bt_event_borrow_class_const(self.inner.inner) })
+    }
+
+    pub fn payload(&self) -> Option<FieldRef<'msg>> {
+        FieldRef::from_raw(unsafe { ffi::bt_event_borrow_payload_field_const(self.inner.inner) })
+    }
+
+    pub fn specific_context(&self) -> Option<FieldRef<'msg>> {
+        FieldRef::from_raw(unsafe {
+            ffi::bt_event_borrow_specific_context_field_const(self.inner.inner)
+        })
+    }
+
+    pub fn common_context(&self) -> Option<FieldRef<'msg>> {
+        FieldRef::from_raw(unsafe {
+            ffi::bt_event_borrow_common_context_field_const(self.inner.inner)
+        })
+    }
+
+    pub fn packet_context(&self) -> Option<FieldRef<'msg>> {
+        let pkt = unsafe { ffi::bt_event_borrow_packet_const(self.inner.inner) };
+        if pkt.is_null() {
+            None
+        } else {
+            FieldRef::from_raw(unsafe { ffi::bt_packet_borrow_context_field_const(pkt) })
+        }
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OwnedEvent {
+    /// Index of the originating trace, see `CtfIterator::trace_properties`.
+    /// Assigned by the iterators, zero when converted on its own.
+    pub trace_index: TraceIndex,
     pub stream_id: StreamId,
     pub clock_snapshot: Option<ClockNanoseconds>,
     pub class_properties: EventClassProperties,
@@ -127,6 +309,7 @@ impl fmt::Display for OwnedEvent {
             format!("ID={}", self.class_properties.id)
         };
         write!(f, "[{}] {}", ns, event_name)?;
+        write!(f, "\n  trace index: {}", self.trace_index)?;
         write!(f, "\n  stream ID: {}", self.stream_id)?;
         if let Some(t) = self.class_properties.log_level {
             write!(f, "\n  log_level: {:?}", t)?;
@@ -148,6 +331,7 @@ impl fmt::Display for OwnedEvent {
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventClassProperties {
     pub id: EventId,
     pub name: Option<String>,
@@ -155,6 +339,7 @@ pub struct EventClassProperties {
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EventProperties {
     pub payload: Option<OwnedField>,
     pub specific_context: Option<OwnedField>,
@@ -162,9 +347,33 @@ pub struct EventProperties {
     pub packet_context: Option<OwnedField>,
 }
 
+impl EventProperties {
+    /// Looks up a field by a dot-separated path, starting with the scope,
+    /// one of `payload`, `specific_context`, `common_context` or `packet_context`.
+    ///
+    /// See `OwnedField::get` for the rest of the path.
+    ///
+    /// Example: `props.get("payload.msg.len")?.as_u64()?`
+    pub fn get(&self, path: &str) -> BtResult<&OwnedField> {
+        let (scope, rest) = path.split_once('.').unwrap_or((path, ""));
+        let root = match scope {
+            "payload" => &self.payload,
+            "specific_context" => &self.specific_context,
+            "common_context" => &self.common_context,
+            "packet_context" => &self.packet_context,
+            _ => return Err(Error::FieldScope(scope.to_owned())),
+        };
+        root.as_ref()
+            .ok_or_else(|| Error::FieldPathNotFound(path.to_owned()))?
+            .get(rest)
+            .map_err(|_| Error::FieldPathNotFound(path.to_owned()))
+    }
+}
+
 pub type EventId = u64;
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventLogLevel {
     Emergency,
     Alert,
@@ -210,3 +419,63 @@ impl EventLogLevel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScalarField;
+
+    #[test]
+    fn properties_path_lookup() {
+        let props = EventProperties {
+            payload: Some(OwnedField::Structure(
+                None,
+                vec![OwnedField::Scalar(
+                    Some("msg".to_owned()),
+                    ScalarField::String("hi".to_owned()),
+                )],
+            )),
+            specific_context: None,
+            common_context: None,
+            packet_context: None,
+        };
+        assert_eq!(props.get("payload.msg").unwrap().as_str(), Ok("hi"));
+        assert!(props.get("payload").is_ok());
+        assert_eq!(
+            props.get("payload.nope"),
+            Err(Error::FieldPathNotFound("payload.nope".to_owned()))
+        );
+        assert_eq!(
+            props.get("common_context.cpu_id"),
+            Err(Error::FieldPathNotFound("common_context.cpu_id".to_owned()))
+        );
+        assert_eq!(
+            props.get("header.msg"),
+            Err(Error::FieldScope("header".to_owned()))
+        );
+    }
+
+    #[test]
+    fn display_shows_trace_index() {
+        let event = OwnedEvent {
+            trace_index: 2,
+            stream_id: 1,
+            clock_snapshot: Some(10),
+            class_properties: EventClassProperties {
+                id: 0,
+                name: Some("ev".to_owned()),
+                log_level: None,
+            },
+            properties: EventProperties {
+                payload: None,
+                specific_context: None,
+                common_context: None,
+                packet_context: None,
+            },
+        };
+        assert_eq!(
+            event.to_string(),
+            "[10] ev (ID=0)\n  trace index: 2\n  stream ID: 1"
+        );
+    }
+}
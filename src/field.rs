@@ -1,6 +1,9 @@
-use crate::{ffi, util, BtResult, BtResultExt};
+use crate::{ffi, util, BtResult, BtResultExt, Error, FieldClass};
 use ordered_float::OrderedFloat;
 use std::collections::BTreeSet;
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::os::raw::c_int;
 use std::{fmt, ptr, slice};
 
 /// Fields are containers of trace data: they are found in events and packets
@@ -39,6 +42,14 @@ impl Field {
                     ScalarField::Bool(v != 0),
                 ))
             }
+            BitArray => {
+                let v = unsafe { ffi::bt_field_bit_array_get_value_as_integer(self.field) };
+                let len = unsafe { ffi::bt_field_class_bit_array_get_length(self.class) };
+                Some(OwnedField::Scalar(
+                    maybe_field_name,
+                    ScalarField::BitArray(v, len),
+                ))
+            }
             UnsignedInteger => {
                 let v = unsafe { ffi::bt_field_integer_unsigned_get_value(self.field) };
                 Some(OwnedField::Scalar(
@@ -183,18 +194,318 @@ impl Field {
                     None
                 }
             }
+            StaticArray | DynamicArrayWithoutLength | DynamicArrayWithLength => {
+                let len = unsafe { ffi::bt_field_array_get_length(self.field) };
+                let mut elements = Vec::with_capacity(len as _);
+                for eidx in 0..len {
+                    let efield = unsafe {
+                        ffi::bt_field_array_borrow_element_field_by_index_const(self.field, eidx)
+                    };
+                    // Elements are kept in place, even when they can't be converted,
+                    // so that indices still match the trace
+                    elements.push(match Field::from_raw(efield) {
+                        Some(f) => f.to_owned_contained(None)?,
+                        None => OwnedField::Unsupported(None),
+                    });
+                }
+
+                Some(OwnedField::Array(maybe_field_name, elements))
+            }
+            OptionWithoutSelector
+            | OptionWithBoolSelector
+            | OptionWithUnsignedIntegerSelector
+            | OptionWithSignedIntegerSelector => {
+                let ofield = unsafe { ffi::bt_field_option_borrow_field_const(self.field) };
+                // A present option is always reported as present, even when its
+                // content can't be converted
+                let content = Field::from_raw(ofield)
+                    .map(|f| f.to_owned_contained(None))
+                    .transpose()?
+                    .map(Box::new);
+                Some(OwnedField::Optional(maybe_field_name, content))
+            }
+            VariantWithoutSelector
+            | VariantWithUnsignedIntegerSelector
+            | VariantWithSignedIntegerSelector => {
+                let vfield =
+                    unsafe { ffi::bt_field_variant_borrow_selected_option_field_const(self.field) };
+                let vclass =
+                    unsafe { ffi::bt_field_variant_borrow_selected_option_class_const(self.field) };
+                let option_index =
+                    unsafe { ffi::bt_field_variant_get_selected_option_index(self.field) };
+                let oname_cstr = unsafe { ffi::bt_field_class_variant_option_get_name(vclass) };
+                let oname = util::opt_owned_cstr(oname_cstr)?;
+                let v = match Field::from_raw(vfield) {
+                    Some(f) => f.to_owned_contained(oname)?,
+                    None => OwnedField::Unsupported(oname),
+                };
+                Some(OwnedField::Variant(
+                    maybe_field_name,
+                    option_index,
+                    Box::new(v),
+                ))
+            }
             Unsupported(typ) => {
                 log::trace!("Skipping unsupported field type {}", typ);
                 None
             }
         })
     }
+
+    /// Array elements and the contents of options and variants must always be
+    /// present, so empty strings and structures are kept rather than skipped,
+    /// and fields that can't be converted are represented by `OwnedField::Unsupported`
+    fn to_owned_contained(&self, maybe_field_name: Option<String>) -> BtResult<OwnedField> {
+        match self.get_type() {
+            FieldType::String => {
+                let raw = unsafe { ffi::bt_field_string_get_value(self.field) };
+                let v = util::opt_owned_cstr(raw)?.unwrap_or_default();
+                Ok(OwnedField::Scalar(maybe_field_name, ScalarField::String(v)))
+            }
+            FieldType::Structure => Ok(self
+                .to_owned_inner(maybe_field_name.clone())?
+                .unwrap_or(OwnedField::Structure(maybe_field_name, Vec::new()))),
+            _ => Ok(self
+                .to_owned_inner(maybe_field_name.clone())?
+                .unwrap_or(OwnedField::Unsupported(maybe_field_name))),
+        }
+    }
+}
+
+/// A field borrowed in place from the message that contains it, nothing is copied
+/// until `to_owned` is called
+#[derive(Copy, Clone)]
+pub struct FieldRef<'msg> {
+    field: *const ffi::bt_field,
+    class: *const ffi::bt_field_class,
+    _msg: PhantomData<&'msg ()>,
+}
+
+impl<'msg> FieldRef<'msg> {
+    pub(crate) fn from_raw(field: *const ffi::bt_field) -> Option<Self> {
+        Field::from_raw(field).map(|f| FieldRef {
+            field: f.field,
+            class: f.class,
+            _msg: PhantomData,
+        })
+    }
+
+    pub fn get_type(&self) -> FieldType {
+        let typ = unsafe { ffi::bt_field_class_get_type(self.class) };
+        FieldType::from_raw(typ)
+    }
+
+    /// The field class (schema) of this field
+    pub fn class(&self) -> FieldClass<'msg> {
+        FieldClass {
+            inner: self.class,
+            _owner: PhantomData,
+        }
+    }
+
+    pub fn to_owned(self) -> BtResult<Option<OwnedField>> {
+        Field {
+            field: self.field,
+            class: self.class,
+        }
+        .to_owned()
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.get_type() {
+            FieldType::Bool => Some(unsafe { ffi::bt_field_bool_get_value(self.field) } != 0),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an unsigned integer, unsigned enumeration or bit array field
+    pub fn as_u64(&self) -> Option<u64> {
+        use FieldType::*;
+        match self.get_type() {
+            UnsignedInteger | UnsignedEnumeration => {
+                Some(unsafe { ffi::bt_field_integer_unsigned_get_value(self.field) })
+            }
+            BitArray => Some(unsafe { ffi::bt_field_bit_array_get_value_as_integer(self.field) }),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a signed integer or signed enumeration field
+    pub fn as_i64(&self) -> Option<i64> {
+        use FieldType::*;
+        match self.get_type() {
+            SignedInteger | SignedEnumeration => {
+                Some(unsafe { ffi::bt_field_integer_signed_get_value(self.field) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a single or double precision real field
+    pub fn as_f64(&self) -> Option<f64> {
+        use FieldType::*;
+        match self.get_type() {
+            SinglePrecisionReal => {
+                Some(unsafe { ffi::bt_field_real_single_precision_get_value(self.field) }.into())
+            }
+            DoublePrecisionReal => {
+                Some(unsafe { ffi::bt_field_real_double_precision_get_value(self.field) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a string field, or None if it isn't valid UTF-8
+    pub fn as_str(&self) -> Option<&'msg str> {
+        match self.get_type() {
+            FieldType::String => {
+                // The string is owned by the field, which lives as long as 'msg
+                unsafe { util::borrowed_cstr(ffi::bt_field_string_get_value(self.field)) }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the labels of the mappings an enumeration field's value belongs to
+    pub fn enumeration_labels(&self) -> BtResult<Option<Vec<&'msg str>>> {
+        let mut labels = ptr::null();
+        let mut count = 0;
+        match self.get_type() {
+            FieldType::UnsignedEnumeration => unsafe {
+                ffi::bt_field_enumeration_unsigned_get_mapping_labels(
+                    self.field,
+                    &mut labels,
+                    &mut count,
+                )
+            }
+            .capi_result()?,
+            FieldType::SignedEnumeration => unsafe {
+                ffi::bt_field_enumeration_signed_get_mapping_labels(
+                    self.field,
+                    &mut labels,
+                    &mut count,
+                )
+            }
+            .capi_result()?,
+            _ => return Ok(None),
+        }
+        let labels_slice = if count == 0 || labels.is_null() {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(labels, count as _) }
+        };
+        Ok(Some(
+            labels_slice
+                .iter()
+                .filter_map(|l| unsafe { util::borrowed_cstr(*l) })
+                .collect(),
+        ))
+    }
+
+    /// Returns the number of members of a structure field
+    pub fn member_count(&self) -> Option<u64> {
+        match self.get_type() {
+            FieldType::Structure => {
+                Some(unsafe { ffi::bt_field_class_structure_get_member_count(self.class) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the name and field of a structure field's member
+    pub fn member_by_index(&self, index: u64) -> Option<(Option<&'msg str>, FieldRef<'msg>)> {
+        if index >= self.member_count()? {
+            return None;
+        }
+        let mclass = unsafe {
+            ffi::bt_field_class_structure_borrow_member_by_index_const(self.class, index)
+        };
+        let mname =
+            unsafe { util::borrowed_cstr(ffi::bt_field_class_structure_member_get_name(mclass)) };
+        let mfield = unsafe {
+            ffi::bt_field_structure_borrow_member_field_by_index_const(self.field, index)
+        };
+        FieldRef::from_raw(mfield).map(|f| (mname, f))
+    }
+
+    pub fn member_by_name(&self, name: &str) -> Option<FieldRef<'msg>> {
+        self.members()
+            .find(|(mname, _)| *mname == Some(name))
+            .map(|(_, f)| f)
+    }
+
+    /// Iterates over the (name, field) members of a structure field,
+    /// empty for any other type of field
+    pub fn members(&self) -> impl Iterator<Item = (Option<&'msg str>, FieldRef<'msg>)> + '_ {
+        (0..self.member_count().unwrap_or(0)).filter_map(move |idx| self.member_by_index(idx))
+    }
+
+    /// Returns the number of elements of an array field
+    pub fn array_len(&self) -> Option<u64> {
+        use FieldType::*;
+        match self.get_type() {
+            StaticArray | DynamicArrayWithoutLength | DynamicArrayWithLength => {
+                Some(unsafe { ffi::bt_field_array_get_length(self.field) })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn element_by_index(&self, index: u64) -> Option<FieldRef<'msg>> {
+        if index >= self.array_len()? {
+            return None;
+        }
+        FieldRef::from_raw(unsafe {
+            ffi::bt_field_array_borrow_element_field_by_index_const(self.field, index)
+        })
+    }
+
+    /// Iterates over the elements of an array field, empty for any other type of field
+    pub fn elements(&self) -> impl Iterator<Item = FieldRef<'msg>> + '_ {
+        (0..self.array_len().unwrap_or(0)).filter_map(move |idx| self.element_by_index(idx))
+    }
+
+    /// Returns the contained field of an option field, None if it isn't an option
+    /// or the option is absent
+    pub fn option_field(&self) -> Option<FieldRef<'msg>> {
+        use FieldType::*;
+        match self.get_type() {
+            OptionWithoutSelector
+            | OptionWithBoolSelector
+            | OptionWithUnsignedIntegerSelector
+            | OptionWithSignedIntegerSelector => {
+                FieldRef::from_raw(unsafe { ffi::bt_field_option_borrow_field_const(self.field) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the selected option name and field of a variant field
+    pub fn variant_selected_option(&self) -> Option<(Option<&'msg str>, FieldRef<'msg>)> {
+        use FieldType::*;
+        match self.get_type() {
+            VariantWithoutSelector
+            | VariantWithUnsignedIntegerSelector
+            | VariantWithSignedIntegerSelector => {
+                let vclass =
+                    unsafe { ffi::bt_field_variant_borrow_selected_option_class_const(self.field) };
+                let oname = unsafe {
+                    util::borrowed_cstr(ffi::bt_field_class_variant_option_get_name(vclass))
+                };
+                let vfield =
+                    unsafe { ffi::bt_field_variant_borrow_selected_option_field_const(self.field) };
+                FieldRef::from_raw(vfield).map(|f| (oname, f))
+            }
+            _ => None,
+        }
+    }
 }
 
 // NOTE: we only support a subset of the available field types
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum FieldType {
     Bool,
+    BitArray,
     UnsignedInteger,
     SignedInteger,
     SinglePrecisionReal,
@@ -203,6 +514,16 @@ pub enum FieldType {
     UnsignedEnumeration,
     SignedEnumeration,
     Structure,
+    StaticArray,
+    DynamicArrayWithoutLength,
+    DynamicArrayWithLength,
+    OptionWithoutSelector,
+    OptionWithBoolSelector,
+    OptionWithUnsignedIntegerSelector,
+    OptionWithSignedIntegerSelector,
+    VariantWithoutSelector,
+    VariantWithUnsignedIntegerSelector,
+    VariantWithSignedIntegerSelector,
     Unsupported(ffi::bt_field_class_type::Type),
 }
 
@@ -212,6 +533,7 @@ impl FieldType {
         use FieldType::*;
         match raw {
             BT_FIELD_CLASS_TYPE_BOOL => Bool,
+            BT_FIELD_CLASS_TYPE_BIT_ARRAY => BitArray,
             BT_FIELD_CLASS_TYPE_UNSIGNED_INTEGER => UnsignedInteger,
             BT_FIELD_CLASS_TYPE_SIGNED_INTEGER => SignedInteger,
             BT_FIELD_CLASS_TYPE_SINGLE_PRECISION_REAL => SinglePrecisionReal,
@@ -220,6 +542,24 @@ impl FieldType {
             BT_FIELD_CLASS_TYPE_UNSIGNED_ENUMERATION => UnsignedEnumeration,
             BT_FIELD_CLASS_TYPE_SIGNED_ENUMERATION => SignedEnumeration,
             BT_FIELD_CLASS_TYPE_STRUCTURE => Structure,
+            BT_FIELD_CLASS_TYPE_STATIC_ARRAY => StaticArray,
+            BT_FIELD_CLASS_TYPE_DYNAMIC_ARRAY_WITHOUT_LENGTH_FIELD => DynamicArrayWithoutLength,
+            BT_FIELD_CLASS_TYPE_DYNAMIC_ARRAY_WITH_LENGTH_FIELD => DynamicArrayWithLength,
+            BT_FIELD_CLASS_TYPE_OPTION_WITHOUT_SELECTOR_FIELD => OptionWithoutSelector,
+            BT_FIELD_CLASS_TYPE_OPTION_WITH_BOOL_SELECTOR_FIELD => OptionWithBoolSelector,
+            BT_FIELD_CLASS_TYPE_OPTION_WITH_UNSIGNED_INTEGER_SELECTOR_FIELD => {
+                OptionWithUnsignedIntegerSelector
+            }
+            BT_FIELD_CLASS_TYPE_OPTION_WITH_SIGNED_INTEGER_SELECTOR_FIELD => {
+                OptionWithSignedIntegerSelector
+            }
+            BT_FIELD_CLASS_TYPE_VARIANT_WITHOUT_SELECTOR_FIELD => VariantWithoutSelector,
+            BT_FIELD_CLASS_TYPE_VARIANT_WITH_UNSIGNED_INTEGER_SELECTOR_FIELD => {
+                VariantWithUnsignedIntegerSelector
+            }
+            BT_FIELD_CLASS_TYPE_VARIANT_WITH_SIGNED_INTEGER_SELECTOR_FIELD => {
+                VariantWithSignedIntegerSelector
+            }
             _ => Unsupported(raw),
         }
     }
@@ -229,12 +569,258 @@ impl FieldType {
     }
 }
 
+/// Index of a variant's selected option in its class, in declaration order.
+///
+/// It is not the selector value: the selector is another field of the event
+/// and any value within the option's ranges selects it.
+pub type VariantOptionIndex = u64;
+
 /// Owned version of a field and its class name (field name, field value)
+///
+/// With the `serde` feature, fields use serde's default externally tagged
+/// enum representation, e.g. `{"Scalar":["len",{"UnsignedInteger":2}]}`,
+/// enumeration labels are a sequence of strings.
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OwnedField {
     Scalar(Option<String>, ScalarField),
     // NOTE: in the future, call this Container, one of structure, array, option, variant
     Structure(Option<String>, Vec<OwnedField>),
+    /// Static or dynamic array, elements are anonymous and in order
+    Array(Option<String>, Vec<OwnedField>),
+    /// Option, the contained field is anonymous and None when absent
+    Optional(Option<String>, Option<Box<OwnedField>>),
+    /// Variant, the selected option's `VariantOptionIndex` and the contained
+    /// field, named after the selected option.
+    /// For CTF variants the selected option name is the label of the tag enumeration.
+    Variant(Option<String>, VariantOptionIndex, Box<OwnedField>),
+    /// Placeholder for an array element, option or variant content of a type
+    /// that isn't supported, so that the containing field keeps its shape
+    Unsupported(Option<String>),
+}
+
+impl OwnedField {
+    pub fn name(&self) -> Option<&str> {
+        use OwnedField::*;
+        match self {
+            Scalar(name, _)
+            | Structure(name, _)
+            | Array(name, _)
+            | Optional(name, _)
+            | Variant(name, _, _)
+            | Unsupported(name) => name.as_deref(),
+        }
+    }
+
+    /// Looks up a descendant field by a dot-separated path, relative to this field.
+    ///
+    /// Path components are structure member names, array element indices, or
+    /// the selected option name of a variant. Present options are looked through.
+    /// An empty path refers to this field.
+    ///
+    /// Example: `payload.get("msg.buf.2")`
+    pub fn get(&self, path: &str) -> BtResult<&OwnedField> {
+        let mut field = self;
+        if path.is_empty() {
+            return Ok(field);
+        }
+        for component in path.split('.') {
+            field = field
+                .child(component)
+                .ok_or_else(|| Error::FieldPathNotFound(path.to_owned()))?;
+        }
+        Ok(field)
+    }
+
+    fn child(&self, component: &str) -> Option<&OwnedField> {
+        use OwnedField::*;
+        match self {
+            Scalar(_, _) | Unsupported(_) => None,
+            Structure(_, members) => members.iter().find(|m| m.name() == Some(component)),
+            Array(_, elements) => elements.get(component.parse::<usize>().ok()?),
+            Optional(_, value) => value.as_deref()?.child(component),
+            Variant(_, _, value) => {
+                if value.name() == Some(component) {
+                    Some(value)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Returns the scalar value, looking through present options
+    pub fn as_scalar(&self) -> BtResult<&ScalarField> {
+        match self {
+            OwnedField::Scalar(_, v) => Ok(v),
+            OwnedField::Optional(_, Some(v)) => v.as_scalar(),
+            _ => Err(self.type_mismatch("a scalar")),
+        }
+    }
+
+    pub fn as_bool(&self) -> BtResult<bool> {
+        match self.as_scalar()? {
+            ScalarField::Bool(v) => Ok(*v),
+            _ => Err(self.type_mismatch("a boolean")),
+        }
+    }
+
+    /// Returns the value of an unsigned integer, unsigned enumeration or bit array field
+    pub fn as_u64(&self) -> BtResult<u64> {
+        use ScalarField::*;
+        match self.as_scalar()? {
+            UnsignedInteger(v) | UnsignedEnumeration(v, _) | BitArray(v, _) => Ok(*v),
+            _ => Err(self.type_mismatch("an unsigned integer")),
+        }
+    }
+
+    /// Returns the value of a signed integer or signed enumeration field
+    pub fn as_i64(&self) -> BtResult<i64> {
+        use ScalarField::*;
+        match self.as_scalar()? {
+            SignedInteger(v) | SignedEnumeration(v, _) => Ok(*v),
+            _ => Err(self.type_mismatch("a signed integer")),
+        }
+    }
+
+    /// Returns the value of a single or double precision real field
+    pub fn as_f64(&self) -> BtResult<f64> {
+        use ScalarField::*;
+        match self.as_scalar()? {
+            SinglePrecisionReal(v) => Ok(v.into_inner().into()),
+            DoublePrecisionReal(v) => Ok(v.into_inner()),
+            _ => Err(self.type_mismatch("a real")),
+        }
+    }
+
+    pub fn as_str(&self) -> BtResult<&str> {
+        match self.as_scalar()? {
+            ScalarField::String(v) => Ok(v.as_str()),
+            _ => Err(self.type_mismatch("a string")),
+        }
+    }
+
+    /// Returns the labels of an enumeration field
+    pub fn as_enumeration_labels(&self) -> BtResult<&BTreeSet<String>> {
+        use ScalarField::*;
+        match self.as_scalar()? {
+            UnsignedEnumeration(_, l) | SignedEnumeration(_, l) => Ok(l),
+            _ => Err(self.type_mismatch("an enumeration")),
+        }
+    }
+
+    /// Returns the members of a structure field
+    pub fn as_structure(&self) -> BtResult<&[OwnedField]> {
+        match self {
+            OwnedField::Structure(_, members) => Ok(members),
+            _ => Err(self.type_mismatch("a structure")),
+        }
+    }
+
+    /// Returns the elements of an array field
+    pub fn as_array(&self) -> BtResult<&[OwnedField]> {
+        match self {
+            OwnedField::Array(_, elements) => Ok(elements),
+            _ => Err(self.type_mismatch("an array")),
+        }
+    }
+
+    fn type_mismatch(&self, expected: &'static str) -> Error {
+        Error::FieldTypeMismatch(self.name().unwrap_or("<anonymous>").to_owned(), expected)
+    }
+
+    /// Write this value into a field of a newly created event or packet.
+    ///
+    /// Structure members are matched by name, variant options by the index
+    /// of the selected option.
+    pub(crate) fn write_to(&self, field: *mut ffi::bt_field) -> BtResult<()> {
+        use ffi::bt_field_class_type::*;
+        let typ = unsafe { ffi::bt_field_get_class_type(field) };
+        let is = |base: ffi::bt_field_class_type::Type| typ & base == base;
+        unsafe {
+            match self {
+                OwnedField::Scalar(_, v) => match v {
+                    ScalarField::Bool(b) if is(BT_FIELD_CLASS_TYPE_BOOL) => {
+                        ffi::bt_field_bool_set_value(field, *b as _)
+                    }
+                    ScalarField::BitArray(v, _) if is(BT_FIELD_CLASS_TYPE_BIT_ARRAY) => {
+                        ffi::bt_field_bit_array_set_value_as_integer(field, *v)
+                    }
+                    ScalarField::UnsignedInteger(v) | ScalarField::UnsignedEnumeration(v, _)
+                        if is(BT_FIELD_CLASS_TYPE_UNSIGNED_INTEGER) =>
+                    {
+                        ffi::bt_field_integer_unsigned_set_value(field, *v)
+                    }
+                    ScalarField::SignedInteger(v) | ScalarField::SignedEnumeration(v, _)
+                        if is(BT_FIELD_CLASS_TYPE_SIGNED_INTEGER) =>
+                    {
+                        ffi::bt_field_integer_signed_set_value(field, *v)
+                    }
+                    ScalarField::SinglePrecisionReal(v)
+                        if is(BT_FIELD_CLASS_TYPE_SINGLE_PRECISION_REAL) =>
+                    {
+                        ffi::bt_field_real_single_precision_set_value(field, v.into_inner())
+                    }
+                    ScalarField::DoublePrecisionReal(v)
+                        if is(BT_FIELD_CLASS_TYPE_DOUBLE_PRECISION_REAL) =>
+                    {
+                        ffi::bt_field_real_double_precision_set_value(field, v.into_inner())
+                    }
+                    ScalarField::String(s) if is(BT_FIELD_CLASS_TYPE_STRING) => {
+                        let s = CString::new(s.as_str())?;
+                        ffi::bt_field_string_set_value(field, s.as_ptr()).capi_result()?
+                    }
+                    _ => return Err(self.type_mismatch("compatible with its field class")),
+                },
+                OwnedField::Structure(_, members) if is(BT_FIELD_CLASS_TYPE_STRUCTURE) => {
+                    for m in members.iter() {
+                        let name = m.name().unwrap_or_default();
+                        let cname = CString::new(name)?;
+                        let member = ffi::bt_field_structure_borrow_member_field_by_name(
+                            field,
+                            cname.as_ptr(),
+                        );
+                        if member.is_null() {
+                            return Err(Error::FieldPathNotFound(name.to_owned()));
+                        }
+                        m.write_to(member)?;
+                    }
+                }
+                OwnedField::Array(_, elements) if is(BT_FIELD_CLASS_TYPE_ARRAY) => {
+                    let len = elements.len() as u64;
+                    if is(BT_FIELD_CLASS_TYPE_DYNAMIC_ARRAY) {
+                        ffi::bt_field_array_dynamic_set_length(field, len).capi_result()?;
+                    } else if ffi::bt_field_array_get_length(field) != len {
+                        return Err(self.type_mismatch("a static array of the same length"));
+                    }
+                    for (idx, e) in elements.iter().enumerate() {
+                        e.write_to(ffi::bt_field_array_borrow_element_field_by_index(
+                            field, idx as u64,
+                        ))?;
+                    }
+                }
+                OwnedField::Optional(_, content) if is(BT_FIELD_CLASS_TYPE_OPTION) => {
+                    ffi::bt_field_option_set_has_field(field, content.is_some() as _);
+                    if let Some(c) = content {
+                        c.write_to(ffi::bt_field_option_borrow_field(field))?;
+                    }
+                }
+                OwnedField::Variant(_, option_index, option) if is(BT_FIELD_CLASS_TYPE_VARIANT) => {
+                    let class = ffi::bt_field_borrow_class_const(field);
+                    if *option_index >= ffi::bt_field_class_variant_get_option_count(class) {
+                        return Err(Error::FieldPathNotFound(
+                            option.name().unwrap_or_default().to_owned(),
+                        ));
+                    }
+                    let status = ffi::bt_field_variant_select_option_by_index(field, *option_index);
+                    (status as c_int).capi_result()?;
+                    option.write_to(ffi::bt_field_variant_borrow_selected_option_field(field))?;
+                }
+                _ => return Err(self.type_mismatch("compatible with its field class")),
+            }
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for OwnedField {
@@ -259,14 +845,58 @@ impl fmt::Display for OwnedField {
                     write!(f, "{{ {} }}", fields_string)
                 }
             }
+            Array(name, elements) => {
+                let elements_string = elements
+                    .iter()
+                    .map(|e| match e {
+                        Scalar(_, v) => v.to_string(),
+                        _ => e.to_string(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                if let Some(n) = name {
+                    write!(f, "{} = [ {} ]", n, elements_string)
+                } else {
+                    write!(f, "[ {} ]", elements_string)
+                }
+            }
+            Optional(name, value) => {
+                let value_string = match value.as_deref() {
+                    Some(Scalar(_, v)) => v.to_string(),
+                    Some(v) => v.to_string(),
+                    None => "<none>".to_owned(),
+                };
+                if let Some(n) = name {
+                    write!(f, "{} = {}", n, value_string)
+                } else {
+                    f.write_str(&value_string)
+                }
+            }
+            Variant(name, _, value) => {
+                if let Some(n) = name {
+                    write!(f, "{} = {{ {} }}", n, value)
+                } else {
+                    write!(f, "{{ {} }}", value)
+                }
+            }
+            Unsupported(name) => {
+                if let Some(n) = name {
+                    write!(f, "{} = <unsupported>", n)
+                } else {
+                    f.write_str("<unsupported>")
+                }
+            }
         }
     }
 }
 
 /// Owned version of a scalar field
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ScalarField {
     Bool(bool),
+    /// Bit array value and length in bits
+    BitArray(u64, u64),
     UnsignedInteger(u64),
     SignedInteger(i64),
     SinglePrecisionReal(OrderedFloat<f32>),
@@ -276,11 +906,27 @@ pub enum ScalarField {
     SignedEnumeration(i64, BTreeSet<String>),
 }
 
+impl ScalarField {
+    /// Returns the indices of the set bits, least significant bit first,
+    /// or None if this isn't a bit array
+    pub fn bit_array_set_bits(&self) -> Option<Vec<u64>> {
+        match self {
+            ScalarField::BitArray(v, len) => Some(
+                (0..(*len).min(u64::BITS as u64))
+                    .filter(|bit| (v >> bit) & 1 == 1)
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for ScalarField {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use ScalarField::*;
         match self {
             Bool(v) => write!(f, "{}", v),
+            BitArray(v, _) => write!(f, "{:#x}", v),
             UnsignedInteger(v) => write!(f, "{}", v),
             SignedInteger(v) => write!(f, "{}", v),
             SinglePrecisionReal(v) => write!(f, "{}", v),
@@ -307,3 +953,214 @@ impl fmt::Display for ScalarField {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn array_display() {
+        let f = OwnedField::Structure(
+            None,
+            vec![
+                OwnedField::Scalar(Some("len".to_owned()), ScalarField::UnsignedInteger(2)),
+                OwnedField::Array(
+                    Some("buf".to_owned()),
+                    vec![
+                        OwnedField::Scalar(None, ScalarField::UnsignedInteger(1)),
+                        OwnedField::Scalar(None, ScalarField::UnsignedInteger(2)),
+                    ],
+                ),
+                OwnedField::Array(
+                    Some("nested".to_owned()),
+                    vec![OwnedField::Array(
+                        None,
+                        vec![OwnedField::Scalar(
+                            None,
+                            ScalarField::String("a".to_owned()),
+                        )],
+                    )],
+                ),
+            ],
+        );
+        assert_eq!(
+            f.to_string(),
+            "{ len = 2, buf = [ 1, 2 ], nested = [ [ 'a' ] ] }"
+        );
+    }
+
+    #[test]
+    fn array_keeps_unsupported_elements() {
+        let f = OwnedField::Array(
+            Some("buf".to_owned()),
+            vec![
+                OwnedField::Unsupported(None),
+                OwnedField::Structure(None, Vec::new()),
+                OwnedField::Scalar(None, ScalarField::UnsignedInteger(3)),
+            ],
+        );
+        assert_eq!(f.to_string(), "buf = [ <unsupported>, {  }, 3 ]");
+        assert_eq!(f.get("0").unwrap(), &OwnedField::Unsupported(None));
+        assert_eq!(f.get("2").unwrap().as_u64(), Ok(3));
+        assert_eq!(
+            f.get("0").unwrap().as_u64(),
+            Err(Error::FieldTypeMismatch(
+                "<anonymous>".to_owned(),
+                "a scalar"
+            ))
+        );
+    }
+
+    #[test]
+    fn path_lookup() {
+        let f = OwnedField::Structure(
+            None,
+            vec![
+                OwnedField::Structure(
+                    Some("msg".to_owned()),
+                    vec![
+                        OwnedField::Scalar(Some("len".to_owned()), ScalarField::UnsignedInteger(2)),
+                        OwnedField::Array(
+                            Some("buf".to_owned()),
+                            vec![
+                                OwnedField::Scalar(None, ScalarField::SignedInteger(-1)),
+                                OwnedField::Scalar(None, ScalarField::SignedInteger(-2)),
+                            ],
+                        ),
+                    ],
+                ),
+                OwnedField::Optional(
+                    Some("opt".to_owned()),
+                    Some(Box::new(OwnedField::Scalar(
+                        None,
+                        ScalarField::String("s".to_owned()),
+                    ))),
+                ),
+                OwnedField::Variant(
+                    Some("v".to_owned()),
+                    0,
+                    Box::new(OwnedField::Scalar(
+                        Some("opt_a".to_owned()),
+                        ScalarField::Bool(true),
+                    )),
+                ),
+            ],
+        );
+        assert_eq!(f.get("").unwrap(), &f);
+        assert_eq!(f.get("msg.len").unwrap().as_u64(), Ok(2));
+        assert_eq!(f.get("msg.buf.1").unwrap().as_i64(), Ok(-2));
+        assert_eq!(f.get("msg.buf").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(f.get("opt").unwrap().as_str(), Ok("s"));
+        assert_eq!(f.get("v.opt_a").unwrap().as_bool(), Ok(true));
+        assert_eq!(
+            f.get("msg.buf.2"),
+            Err(Error::FieldPathNotFound("msg.buf.2".to_owned()))
+        );
+        assert_eq!(
+            f.get("v.opt_b"),
+            Err(Error::FieldPathNotFound("v.opt_b".to_owned()))
+        );
+        assert_eq!(
+            f.get("msg.len").unwrap().as_str(),
+            Err(Error::FieldTypeMismatch("len".to_owned(), "a string"))
+        );
+        assert_eq!(
+            f.get("msg").unwrap().as_u64(),
+            Err(Error::FieldTypeMismatch("msg".to_owned(), "a scalar"))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let f = OwnedField::Structure(
+            None,
+            vec![
+                OwnedField::Scalar(
+                    Some("state".to_owned()),
+                    ScalarField::UnsignedEnumeration(
+                        1,
+                        ["RUNNING".to_owned(), "ACTIVE".to_owned()].into(),
+                    ),
+                ),
+                OwnedField::Scalar(
+                    Some("ratio".to_owned()),
+                    ScalarField::DoublePrecisionReal(0.5.into()),
+                ),
+                OwnedField::Array(
+                    Some("flags".to_owned()),
+                    vec![OwnedField::Scalar(None, ScalarField::BitArray(0x5, 4))],
+                ),
+            ],
+        );
+        let json = serde_json::to_string(&f).unwrap();
+        assert_eq!(
+            json,
+            r#"{"Structure":[null,[{"Scalar":["state",{"UnsignedEnumeration":[1,["ACTIVE","RUNNING"]]}]},{"Scalar":["ratio",{"DoublePrecisionReal":0.5}]},{"Array":["flags",[{"Scalar":[null,{"BitArray":[5,4]}]}]]}]]}"#
+        );
+        assert_eq!(serde_json::from_str::<OwnedField>(&json).unwrap(), f);
+    }
+
+    #[test]
+    fn bit_array_set_bits() {
+        let f = ScalarField::BitArray(0b1000_0101, 8);
+        assert_eq!(f.bit_array_set_bits(), Some(vec![0, 2, 7]));
+        assert_eq!(f.to_string(), "0x85");
+        assert_eq!(
+            ScalarField::BitArray(0, 64).bit_array_set_bits(),
+            Some(vec![])
+        );
+        assert_eq!(
+            ScalarField::BitArray(u64::MAX, 64)
+                .bit_array_set_bits()
+                .map(|b| b.len()),
+            Some(64)
+        );
+        assert_eq!(ScalarField::Bool(true).bit_array_set_bits(), None);
+    }
+
+    #[test]
+    fn option_and_variant_display() {
+        let f = OwnedField::Structure(
+            None,
+            vec![
+                OwnedField::Optional(
+                    Some("present".to_owned()),
+                    Some(Box::new(OwnedField::Scalar(
+                        None,
+                        ScalarField::SignedInteger(-1),
+                    ))),
+                ),
+                OwnedField::Optional(Some("absent".to_owned()), None),
+                OwnedField::Optional(
+                    Some("opaque".to_owned()),
+                    Some(Box::new(OwnedField::Unsupported(None))),
+                ),
+                OwnedField::Variant(
+                    Some("v".to_owned()),
+                    0,
+                    Box::new(OwnedField::Scalar(
+                        Some("opt_a".to_owned()),
+                        ScalarField::Bool(true),
+                    )),
+                ),
+            ],
+        );
+        assert_eq!(
+            f.to_string(),
+            "{ present = -1, absent = <none>, opaque = <unsupported>, v = { opt_a = true } }"
+        );
+        assert!(f.get("opaque").is_ok());
+        assert_eq!(
+            f.get("v"),
+            Ok(&OwnedField::Variant(
+                Some("v".to_owned()),
+                0,
+                Box::new(OwnedField::Scalar(
+                    Some("opt_a".to_owned()),
+                    ScalarField::Bool(true)
+                ))
+            ))
+        );
+    }
+}
@@ -0,0 +1,664 @@
+use crate::{ffi, util, BtResult, BtResultExt, Error, FieldType};
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::ptr;
+
+/// Field classes describe the layout and types of fields (the schema),
+/// they're available without decoding any events.
+///
+/// The class is borrowed from the event class, event or field it belongs to.
+#[derive(Copy, Clone)]
+pub struct FieldClass<'a> {
+    pub(crate) inner: *const ffi::bt_field_class,
+    pub(crate) _owner: PhantomData<&'a ()>,
+}
+
+impl<'a> FieldClass<'a> {
+    pub(crate) fn from_raw(class: *const ffi::bt_field_class) -> Option<Self> {
+        if class.is_null() {
+            None
+        } else {
+            FieldClass {
+                inner: class,
+                _owner: PhantomData,
+            }
+            .into()
+        }
+    }
+
+    pub fn get_type(&self) -> FieldType {
+        let typ = unsafe { ffi::bt_field_class_get_type(self.inner) };
+        FieldType::from_raw(typ)
+    }
+
+    pub fn to_owned(&self) -> BtResult<OwnedFieldClass> {
+        use FieldType::*;
+        let class = self.inner;
+        Ok(match self.get_type() {
+            Bool => OwnedFieldClass::Bool,
+            BitArray => OwnedFieldClass::BitArray(unsafe {
+                ffi::bt_field_class_bit_array_get_length(class)
+            }),
+            UnsignedInteger => OwnedFieldClass::UnsignedInteger(self.integer()),
+            SignedInteger => OwnedFieldClass::SignedInteger(self.integer()),
+            UnsignedEnumeration => {
+                let count = unsafe { ffi::bt_field_class_enumeration_get_mapping_count(class) };
+                let mut mappings = Vec::with_capacity(count as _);
+                for idx in 0..count {
+                    let mapping = unsafe {
+                        ffi::bt_field_class_enumeration_unsigned_borrow_mapping_by_index_const(
+                            class, idx,
+                        )
+                    };
+                    let label = util::opt_owned_cstr(unsafe {
+                        ffi::bt_field_class_enumeration_mapping_get_label(mapping as *const _)
+                    })?
+                    .unwrap_or_default();
+                    let ranges = IntegerRangeSet::from_unsigned(unsafe {
+                        ffi::bt_field_class_enumeration_unsigned_mapping_borrow_ranges_const(
+                            mapping,
+                        )
+                    });
+                    mappings.push(EnumerationMapping { label, ranges });
+                }
+                OwnedFieldClass::UnsignedEnumeration(self.integer(), mappings)
+            }
+            SignedEnumeration => {
+                let count = unsafe { ffi::bt_field_class_enumeration_get_mapping_count(class) };
+                let mut mappings = Vec::with_capacity(count as _);
+                for idx in 0..count {
+                    let mapping = unsafe {
+                        ffi::bt_field_class_enumeration_signed_borrow_mapping_by_index_const(
+                            class, idx,
+                        )
+                    };
+                    let label = util::opt_owned_cstr(unsafe {
+                        ffi::bt_field_class_enumeration_mapping_get_label(mapping as *const _)
+                    })?
+                    .unwrap_or_default();
+                    let ranges = IntegerRangeSet::from_signed(unsafe {
+                        ffi::bt_field_class_enumeration_signed_mapping_borrow_ranges_const(mapping)
+                    });
+                    mappings.push(EnumerationMapping { label, ranges });
+                }
+                OwnedFieldClass::SignedEnumeration(self.integer(), mappings)
+            }
+            SinglePrecisionReal => OwnedFieldClass::SinglePrecisionReal,
+            DoublePrecisionReal => OwnedFieldClass::DoublePrecisionReal,
+            String => OwnedFieldClass::String,
+            Structure => {
+                let count = unsafe { ffi::bt_field_class_structure_get_member_count(class) };
+                let mut members = Vec::with_capacity(count as _);
+                for idx in 0..count {
+                    let member = unsafe {
+                        ffi::bt_field_class_structure_borrow_member_by_index_const(class, idx)
+                    };
+                    let name = util::opt_owned_cstr(unsafe {
+                        ffi::bt_field_class_structure_member_get_name(member)
+                    })?;
+                    let mclass = unsafe {
+                        ffi::bt_field_class_structure_member_borrow_field_class_const(member)
+                    };
+                    members.push(StructureMemberClass {
+                        name,
+                        class: Self::owned_from_raw(mclass)?,
+                    });
+                }
+                OwnedFieldClass::Structure(members)
+            }
+            StaticArray => {
+                let length = unsafe { ffi::bt_field_class_array_static_get_length(class) };
+                OwnedFieldClass::StaticArray(self.array_element()?, length)
+            }
+            DynamicArrayWithoutLength => OwnedFieldClass::DynamicArray(self.array_element()?, None),
+            DynamicArrayWithLength => {
+                let path = FieldPath::from_raw(unsafe {
+                    ffi::bt_field_class_array_dynamic_with_length_field_borrow_length_field_path_const(
+                        class,
+                    )
+                });
+                OwnedFieldClass::DynamicArray(self.array_element()?, path)
+            }
+            OptionWithoutSelector => {
+                OwnedFieldClass::Optional(self.option_content()?, OptionSelector::WithoutSelector)
+            }
+            OptionWithBoolSelector => {
+                let reversed = unsafe {
+                    ffi::bt_field_class_option_with_selector_field_bool_selector_is_reversed(class)
+                } != 0;
+                OwnedFieldClass::Optional(
+                    self.option_content()?,
+                    OptionSelector::Bool(self.option_selector_path(), reversed),
+                )
+            }
+            OptionWithUnsignedIntegerSelector => {
+                let ranges = IntegerRangeSet::from_unsigned(unsafe {
+                    ffi::bt_field_class_option_with_selector_field_integer_unsigned_borrow_selector_ranges_const(class)
+                });
+                OwnedFieldClass::Optional(
+                    self.option_content()?,
+                    OptionSelector::Integer(self.option_selector_path(), ranges),
+                )
+            }
+            OptionWithSignedIntegerSelector => {
+                let ranges = IntegerRangeSet::from_signed(unsafe {
+                    ffi::bt_field_class_option_with_selector_field_integer_signed_borrow_selector_ranges_const(class)
+                });
+                OwnedFieldClass::Optional(
+                    self.option_content()?,
+                    OptionSelector::Integer(self.option_selector_path(), ranges),
+                )
+            }
+            VariantWithoutSelector => OwnedFieldClass::Variant(self.variant_options()?, None),
+            VariantWithUnsignedIntegerSelector | VariantWithSignedIntegerSelector => {
+                let path = FieldPath::from_raw(unsafe {
+                    ffi::bt_field_class_variant_with_selector_field_borrow_selector_field_path_const(
+                        class,
+                    )
+                });
+                OwnedFieldClass::Variant(self.variant_options()?, path)
+            }
+            Unsupported(typ) => OwnedFieldClass::Unsupported(typ),
+        })
+    }
+
+    pub(crate) fn opt_owned_from_raw(
+        class: *const ffi::bt_field_class,
+    ) -> BtResult<Option<OwnedFieldClass>> {
+        FieldClass::from_raw(class)
+            .map(|c| c.to_owned())
+            .transpose()
+    }
+
+    /// Member, element, content and option classes are always present,
+    /// a missing one can't be borrowed
+    fn owned_from_raw(class: *const ffi::bt_field_class) -> BtResult<OwnedFieldClass> {
+        FieldClass::from_raw(class)
+            .ok_or(Error::ResourceBorrow)?
+            .to_owned()
+    }
+
+    fn integer(&self) -> IntegerFieldClass {
+        let field_value_range =
+            unsafe { ffi::bt_field_class_integer_get_field_value_range(self.inner) };
+        let base = unsafe { ffi::bt_field_class_integer_get_preferred_display_base(self.inner) };
+        IntegerFieldClass {
+            field_value_range,
+            preferred_display_base: DisplayBase::from_raw(base),
+        }
+    }
+
+    fn array_element(&self) -> BtResult<Box<OwnedFieldClass>> {
+        let eclass =
+            unsafe { ffi::bt_field_class_array_borrow_element_field_class_const(self.inner) };
+        Ok(Box::new(Self::owned_from_raw(eclass)?))
+    }
+
+    fn option_content(&self) -> BtResult<Box<OwnedFieldClass>> {
+        let oclass = unsafe { ffi::bt_field_class_option_borrow_field_class_const(self.inner) };
+        Ok(Box::new(Self::owned_from_raw(oclass)?))
+    }
+
+    fn option_selector_path(&self) -> Option<FieldPath> {
+        FieldPath::from_raw(unsafe {
+            ffi::bt_field_class_option_with_selector_field_borrow_selector_field_path_const(
+                self.inner,
+            )
+        })
+    }
+
+    fn variant_options(&self) -> BtResult<Vec<VariantOptionClass>> {
+        let typ = self.get_type();
+        let count = unsafe { ffi::bt_field_class_variant_get_option_count(self.inner) };
+        let mut options = Vec::with_capacity(count as _);
+        for idx in 0..count {
+            let option = unsafe {
+                ffi::bt_field_class_variant_borrow_option_by_index_const(self.inner, idx)
+            };
+            let name = util::opt_owned_cstr(unsafe {
+                ffi::bt_field_class_variant_option_get_name(option)
+            })?;
+            let oclass =
+                unsafe { ffi::bt_field_class_variant_option_borrow_field_class_const(option) };
+            let selector_ranges = match typ {
+                FieldType::VariantWithUnsignedIntegerSelector => {
+                    Some(IntegerRangeSet::from_unsigned(unsafe {
+                        let o = ffi::bt_field_class_variant_with_selector_field_integer_unsigned_borrow_option_by_index_const(self.inner, idx);
+                        ffi::bt_field_class_variant_with_selector_field_integer_unsigned_option_borrow_ranges_const(o)
+                    }))
+                }
+                FieldType::VariantWithSignedIntegerSelector => {
+                    Some(IntegerRangeSet::from_signed(unsafe {
+                        let o = ffi::bt_field_class_variant_with_selector_field_integer_signed_borrow_option_by_index_const(self.inner, idx);
+                        ffi::bt_field_class_variant_with_selector_field_integer_signed_option_borrow_ranges_const(o)
+                    }))
+                }
+                _ => None,
+            };
+            options.push(VariantOptionClass {
+                name,
+                class: Self::owned_from_raw(oclass)?,
+                selector_ranges,
+            });
+        }
+        Ok(options)
+    }
+}
+
+/// Owned version of a field class
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedFieldClass {
+    Bool,
+    /// Bit array length in bits
+    BitArray(u64),
+    UnsignedInteger(IntegerFieldClass),
+    SignedInteger(IntegerFieldClass),
+    UnsignedEnumeration(IntegerFieldClass, Vec<EnumerationMapping>),
+    SignedEnumeration(IntegerFieldClass, Vec<EnumerationMapping>),
+    SinglePrecisionReal,
+    DoublePrecisionReal,
+    String,
+    Structure(Vec<StructureMemberClass>),
+    /// Element class and length
+    StaticArray(Box<OwnedFieldClass>, u64),
+    /// Element class and the path to the length field, if any
+    DynamicArray(Box<OwnedFieldClass>, Option<FieldPath>),
+    /// Content class and selector
+    Optional(Box<OwnedFieldClass>, OptionSelector),
+    /// Options and the path to the selector field, if any
+    Variant(Vec<VariantOptionClass>, Option<FieldPath>),
+    Unsupported(ffi::bt_field_class_type::Type),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegerFieldClass {
+    /// Size, in bits, of the range of possible values
+    pub field_value_range: u64,
+    pub preferred_display_base: DisplayBase,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DisplayBase {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+impl DisplayBase {
+    fn from_raw(base: ffi::bt_field_class_integer_preferred_display_base::Type) -> Self {
+        use ffi::bt_field_class_integer_preferred_display_base::*;
+        match base {
+            BT_FIELD_CLASS_INTEGER_PREFERRED_DISPLAY_BASE_BINARY => DisplayBase::Binary,
+            BT_FIELD_CLASS_INTEGER_PREFERRED_DISPLAY_BASE_OCTAL => DisplayBase::Octal,
+            BT_FIELD_CLASS_INTEGER_PREFERRED_DISPLAY_BASE_HEXADECIMAL => DisplayBase::Hexadecimal,
+            _ => DisplayBase::Decimal,
+        }
+    }
+
+    fn to_raw(self) -> ffi::bt_field_class_integer_preferred_display_base::Type {
+        use ffi::bt_field_class_integer_preferred_display_base::*;
+        match self {
+            DisplayBase::Binary => BT_FIELD_CLASS_INTEGER_PREFERRED_DISPLAY_BASE_BINARY,
+            DisplayBase::Octal => BT_FIELD_CLASS_INTEGER_PREFERRED_DISPLAY_BASE_OCTAL,
+            DisplayBase::Decimal => BT_FIELD_CLASS_INTEGER_PREFERRED_DISPLAY_BASE_DECIMAL,
+            DisplayBase::Hexadecimal => BT_FIELD_CLASS_INTEGER_PREFERRED_DISPLAY_BASE_HEXADECIMAL,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnumerationMapping {
+    pub label: String,
+    pub ranges: IntegerRangeSet,
+}
+
+/// Inclusive integer range
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegerRange<T> {
+    pub lower: T,
+    pub upper: T,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegerRangeSet {
+    Unsigned(Vec<IntegerRange<u64>>),
+    Signed(Vec<IntegerRange<i64>>),
+}
+
+impl IntegerRangeSet {
+    fn from_unsigned(set: *const ffi::bt_integer_range_set_unsigned) -> Self {
+        let mut ranges = Vec::new();
+        if !set.is_null() {
+            let count = unsafe { ffi::bt_integer_range_set_get_range_count(set as *const _) };
+            for idx in 0..count {
+                let r = unsafe {
+                    ffi::bt_integer_range_set_unsigned_borrow_range_by_index_const(set, idx)
+                };
+                ranges.push(IntegerRange {
+                    lower: unsafe { ffi::bt_integer_range_unsigned_get_lower(r) },
+                    upper: unsafe { ffi::bt_integer_range_unsigned_get_upper(r) },
+                });
+            }
+        }
+        IntegerRangeSet::Unsigned(ranges)
+    }
+
+    fn from_signed(set: *const ffi::bt_integer_range_set_signed) -> Self {
+        let mut ranges = Vec::new();
+        if !set.is_null() {
+            let count = unsafe { ffi::bt_integer_range_set_get_range_count(set as *const _) };
+            for idx in 0..count {
+                let r = unsafe {
+                    ffi::bt_integer_range_set_signed_borrow_range_by_index_const(set, idx)
+                };
+                ranges.push(IntegerRange {
+                    lower: unsafe { ffi::bt_integer_range_signed_get_lower(r) },
+                    upper: unsafe { ffi::bt_integer_range_signed_get_upper(r) },
+                });
+            }
+        }
+        IntegerRangeSet::Signed(ranges)
+    }
+
+    pub fn contains_unsigned(&self, value: u64) -> bool {
+        match self {
+            IntegerRangeSet::Unsigned(r) => r.iter().any(|r| r.lower <= value && value <= r.upper),
+            IntegerRangeSet::Signed(_) => false,
+        }
+    }
+
+    pub fn contains_signed(&self, value: i64) -> bool {
+        match self {
+            IntegerRangeSet::Signed(r) => r.iter().any(|r| r.lower <= value && value <= r.upper),
+            IntegerRangeSet::Unsigned(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructureMemberClass {
+    pub name: Option<String>,
+    pub class: OwnedFieldClass,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariantOptionClass {
+    pub name: Option<String>,
+    pub class: OwnedFieldClass,
+    /// Selector values which select this option, for variants with an integer selector
+    pub selector_ranges: Option<IntegerRangeSet>,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OptionSelector {
+    WithoutSelector,
+    /// Path to the boolean selector field and whether the selector is reversed
+    Bool(Option<FieldPath>, bool),
+    /// Path to the integer selector field and the selector values for which the option is present
+    Integer(Option<FieldPath>, IntegerRangeSet),
+}
+
+/// Location of a field (length or selector) relative to a root scope
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldPath {
+    pub root: FieldPathScope,
+    pub items: Vec<FieldPathItem>,
+}
+
+impl FieldPath {
+    fn from_raw(path: *const ffi::bt_field_path) -> Option<Self> {
+        use ffi::bt_field_path_item_type::*;
+        use ffi::bt_field_path_scope::*;
+        if path.is_null() {
+            return None;
+        }
+        let root = match unsafe { ffi::bt_field_path_get_root_scope(path) } {
+            BT_FIELD_PATH_SCOPE_PACKET_CONTEXT => FieldPathScope::PacketContext,
+            BT_FIELD_PATH_SCOPE_EVENT_COMMON_CONTEXT => FieldPathScope::EventCommonContext,
+            BT_FIELD_PATH_SCOPE_EVENT_SPECIFIC_CONTEXT => FieldPathScope::EventSpecificContext,
+            _ => FieldPathScope::EventPayload,
+        };
+        let count = unsafe { ffi::bt_field_path_get_item_count(path) };
+        let items = (0..count)
+            .map(|idx| {
+                let item = unsafe { ffi::bt_field_path_borrow_item_by_index_const(path, idx) };
+                match unsafe { ffi::bt_field_path_item_get_type(item) } {
+                    BT_FIELD_PATH_ITEM_TYPE_INDEX => FieldPathItem::Index(unsafe {
+                        ffi::bt_field_path_item_index_get_index(item)
+                    }),
+                    BT_FIELD_PATH_ITEM_TYPE_CURRENT_ARRAY_ELEMENT => {
+                        FieldPathItem::CurrentArrayElement
+                    }
+                    _ => FieldPathItem::CurrentOptionContent,
+                }
+            })
+            .collect();
+        Some(FieldPath { root, items })
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldPathScope {
+    PacketContext,
+    EventCommonContext,
+    EventSpecificContext,
+    EventPayload,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldPathItem {
+    /// Index of a structure member or variant option
+    Index(u64),
+    CurrentArrayElement,
+    CurrentOptionContent,
+}
+
+/// A new field class, the reference is put on drop, once it's
+/// been handed to its parent
+pub(crate) struct RawFieldClass(pub(crate) *mut ffi::bt_field_class);
+
+impl Drop for RawFieldClass {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_field_class_put_ref(self.0) };
+    }
+}
+
+impl OwnedFieldClass {
+    /// Create a field class with this layout in `trace_class`.
+    ///
+    /// Dynamic arrays with a length field and options and variants with
+    /// a selector field aren't supported.
+    pub(crate) fn create(&self, trace_class: *mut ffi::bt_trace_class) -> BtResult<RawFieldClass> {
+        use OwnedFieldClass::*;
+        let tc = trace_class;
+        let fc = unsafe {
+            match self {
+                Bool => created(ffi::bt_field_class_bool_create(tc))?,
+                BitArray(len) => created(ffi::bt_field_class_bit_array_create(tc, *len))?,
+                UnsignedInteger(int) => {
+                    int.apply(created(ffi::bt_field_class_integer_unsigned_create(tc))?)
+                }
+                SignedInteger(int) => {
+                    int.apply(created(ffi::bt_field_class_integer_signed_create(tc))?)
+                }
+                UnsignedEnumeration(int, mappings) | SignedEnumeration(int, mappings) => {
+                    let fc = int.apply(created(if matches!(self, UnsignedEnumeration(..)) {
+                        ffi::bt_field_class_enumeration_unsigned_create(tc)
+                    } else {
+                        ffi::bt_field_class_enumeration_signed_create(tc)
+                    })?);
+                    for m in mappings.iter() {
+                        m.add_to(&fc)?;
+                    }
+                    fc
+                }
+                SinglePrecisionReal => {
+                    created(ffi::bt_field_class_real_single_precision_create(tc))?
+                }
+                DoublePrecisionReal => {
+                    created(ffi::bt_field_class_real_double_precision_create(tc))?
+                }
+                String => created(ffi::bt_field_class_string_create(tc))?,
+                Structure(members) => {
+                    let fc = created(ffi::bt_field_class_structure_create(tc))?;
+                    for m in members.iter() {
+                        let name = CString::new(m.name.as_deref().unwrap_or_default())?;
+                        let member = m.class.create(tc)?;
+                        ffi::bt_field_class_structure_append_member(fc.0, name.as_ptr(), member.0)
+                            .capi_result()?;
+                    }
+                    fc
+                }
+                StaticArray(element, len) => {
+                    let element = element.create(tc)?;
+                    created(ffi::bt_field_class_array_static_create(tc, element.0, *len))?
+                }
+                DynamicArray(element, None) => {
+                    let element = element.create(tc)?;
+                    created(ffi::bt_field_class_array_dynamic_create(
+                        tc,
+                        element.0,
+                        ptr::null_mut(),
+                    ))?
+                }
+                Optional(content, OptionSelector::WithoutSelector) => {
+                    let content = content.create(tc)?;
+                    created(ffi::bt_field_class_option_without_selector_create(
+                        tc, content.0,
+                    ))?
+                }
+                Variant(options, None) => {
+                    let fc = created(ffi::bt_field_class_variant_create(tc, ptr::null_mut()))?;
+                    for opt in options.iter() {
+                        let name = CString::new(opt.name.as_deref().unwrap_or_default())?;
+                        let class = opt.class.create(tc)?;
+                        ffi::bt_field_class_variant_without_selector_append_option(
+                            fc.0,
+                            name.as_ptr(),
+                            class.0,
+                        )
+                        .capi_result()?;
+                    }
+                    fc
+                }
+                DynamicArray(..) | Optional(..) | Variant(..) | Unsupported(_) => {
+                    return Err(Error::UnsupportedFieldClass(format!("{:?}", self)))
+                }
+            }
+        };
+        Ok(fc)
+    }
+}
+
+fn created(fc: *mut ffi::bt_field_class) -> BtResult<RawFieldClass> {
+    if fc.is_null() {
+        Err(Error::Memory)
+    } else {
+        Ok(RawFieldClass(fc))
+    }
+}
+
+impl IntegerFieldClass {
+    fn apply(&self, fc: RawFieldClass) -> RawFieldClass {
+        unsafe {
+            ffi::bt_field_class_integer_set_field_value_range(fc.0, self.field_value_range);
+            ffi::bt_field_class_integer_set_preferred_display_base(
+                fc.0,
+                self.preferred_display_base.to_raw(),
+            );
+        }
+        fc
+    }
+}
+
+impl EnumerationMapping {
+    fn add_to(&self, fc: &RawFieldClass) -> BtResult<()> {
+        let label = CString::new(self.label.as_str())?;
+        unsafe {
+            match &self.ranges {
+                IntegerRangeSet::Unsigned(ranges) => {
+                    let set = ffi::bt_integer_range_set_unsigned_create();
+                    if set.is_null() {
+                        return Err(Error::Memory);
+                    }
+                    let res = ranges
+                        .iter()
+                        .try_for_each(|r| {
+                            ffi::bt_integer_range_set_unsigned_add_range(set, r.lower, r.upper)
+                                .capi_result()
+                        })
+                        .and_then(|_| {
+                            ffi::bt_field_class_enumeration_unsigned_add_mapping(
+                                fc.0,
+                                label.as_ptr(),
+                                set,
+                            )
+                            .capi_result()
+                        });
+                    ffi::bt_integer_range_set_unsigned_put_ref(set);
+                    res
+                }
+                IntegerRangeSet::Signed(ranges) => {
+                    let set = ffi::bt_integer_range_set_signed_create();
+                    if set.is_null() {
+                        return Err(Error::Memory);
+                    }
+                    let res = ranges
+                        .iter()
+                        .try_for_each(|r| {
+                            ffi::bt_integer_range_set_signed_add_range(set, r.lower, r.upper)
+                                .capi_result()
+                        })
+                        .and_then(|_| {
+                            ffi::bt_field_class_enumeration_signed_add_mapping(
+                                fc.0,
+                                label.as_ptr(),
+                                set,
+                            )
+                            .capi_result()
+                        });
+                    ffi::bt_integer_range_set_signed_put_ref(set);
+                    res
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_set_contains() {
+        let set = IntegerRangeSet::Unsigned(vec![
+            IntegerRange { lower: 1, upper: 3 },
+            IntegerRange { lower: 8, upper: 8 },
+        ]);
+        assert!(set.contains_unsigned(1));
+        assert!(set.contains_unsigned(8));
+        assert!(!set.contains_unsigned(4));
+        assert!(!set.contains_signed(2));
+
+        let set = IntegerRangeSet::Signed(vec![IntegerRange {
+            lower: -2,
+            upper: 2,
+        }]);
+        assert!(set.contains_signed(-2));
+        assert!(!set.contains_signed(3));
+        assert!(!set.contains_unsigned(0));
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{BtResult, Error, OwnedEvent, OwnedField, ScalarField};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor};
+use std::fmt;
+
+/// A serde deserializer over an owned field tree.
+///
+/// * structures map to structs and maps, keyed by member name
+/// * arrays map to sequences
+/// * options map to `Option`
+/// * enumerations map to integers, or to their first label when a string
+///   or unit enum variant is requested
+/// * variants map to enums named after the selected option, or to a single entry map
+///   when the type is self-describing
+/// * unsupported placeholders map to unit
+///
+/// Missing structure members are reported with their path, see `Error::FieldMissing`.
+pub struct FieldDeserializer<'de> {
+    field: &'de OwnedField,
+    path: String,
+}
+
+impl<'de> FieldDeserializer<'de> {
+    pub fn new(field: &'de OwnedField) -> Self {
+        Self::with_path(field, field.name().unwrap_or("<anonymous>").to_owned())
+    }
+
+    /// Like `new`, with the path of the field used in errors, e.g. its scope
+    pub fn with_path(field: &'de OwnedField, path: String) -> Self {
+        FieldDeserializer { field, path }
+    }
+
+    fn child(&self, field: &'de OwnedField, component: &str) -> Self {
+        Self::with_path(field, format!("{}.{}", self.path, component))
+    }
+
+    fn contained(&self, field: &'de OwnedField) -> Self {
+        Self::with_path(field, self.path.clone())
+    }
+
+    /// Missing members are reported by the visitor without their location,
+    /// attach the path of the structure they're missing from
+    fn locate(&self, e: Error) -> Error {
+        match e {
+            Error::FieldMissing(path, member) if path.is_empty() => {
+                Error::FieldMissing(self.path.clone(), member)
+            }
+            e => e,
+        }
+    }
+
+    fn first_label(&self) -> Option<&'de str> {
+        match self.field.as_scalar().ok()? {
+            ScalarField::UnsignedEnumeration(_, l) | ScalarField::SignedEnumeration(_, l) => {
+                l.iter().next().map(|s| s.as_str())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl OwnedField {
+    /// Deserialize this field into a user-defined type, see `FieldDeserializer`
+    pub fn deserialize_into<'de, T: Deserialize<'de>>(&'de self) -> BtResult<T> {
+        T::deserialize(FieldDeserializer::new(self))
+    }
+}
+
+impl OwnedEvent {
+    pub fn payload_as<'de, T: Deserialize<'de>>(&'de self) -> BtResult<T> {
+        deserialize_scope(&self.properties.payload, "payload")
+    }
+
+    pub fn specific_context_as<'de, T: Deserialize<'de>>(&'de self) -> BtResult<T> {
+        deserialize_scope(&self.properties.specific_context, "specific_context")
+    }
+
+    pub fn common_context_as<'de, T: Deserialize<'de>>(&'de self) -> BtResult<T> {
+        deserialize_scope(&self.properties.common_context, "common_context")
+    }
+
+    pub fn packet_context_as<'de, T: Deserialize<'de>>(&'de self) -> BtResult<T> {
+        deserialize_scope(&self.properties.packet_context, "packet_context")
+    }
+}
+
+fn deserialize_scope<'de, T: Deserialize<'de>>(
+    field: &'de Option<OwnedField>,
+    scope: &str,
+) -> BtResult<T> {
+    let field = field
+        .as_ref()
+        .ok_or_else(|| Error::FieldPathNotFound(scope.to_owned()))?;
+    T::deserialize(FieldDeserializer::with_path(field, scope.to_owned()))
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::FieldDeserialize(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        // The path is filled in by the deserializer of the containing structure
+        Error::FieldMissing(String::new(), field)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for FieldDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> BtResult<V::Value> {
+        use ScalarField::*;
+        match self.field {
+            OwnedField::Scalar(_, v) => match v {
+                Bool(v) => visitor.visit_bool(*v),
+                BitArray(v, _) | UnsignedInteger(v) | UnsignedEnumeration(v, _) => {
+                    visitor.visit_u64(*v)
+                }
+                SignedInteger(v) | SignedEnumeration(v, _) => visitor.visit_i64(*v),
+                SinglePrecisionReal(v) => visitor.visit_f32(v.into_inner()),
+                DoublePrecisionReal(v) => visitor.visit_f64(v.into_inner()),
+                String(v) => visitor.visit_borrowed_str(v),
+            },
+            OwnedField::Structure(_, members) => {
+                let mut map = MapDeserializer::new(members.iter().map(|m| {
+                    let name = m.name().unwrap_or_default();
+                    (name, self.child(m, name))
+                }));
+                let value = visitor.visit_map(&mut map).map_err(|e| self.locate(e))?;
+                map.end()?;
+                Ok(value)
+            }
+            OwnedField::Array(_, elements) => {
+                let mut seq = SeqDeserializer::new(
+                    elements
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, e)| self.child(e, &idx.to_string())),
+                );
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            OwnedField::Optional(_, None) => visitor.visit_none(),
+            OwnedField::Optional(_, Some(v)) => visitor.visit_some(self.contained(v)),
+            OwnedField::Variant(_, _, v) => {
+                let name = v.name().unwrap_or_default();
+                let mut map = MapDeserializer::new(std::iter::once((name, self.child(v, name))));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            OwnedField::Unsupported(_) => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> BtResult<V::Value> {
+        match self.field {
+            OwnedField::Optional(_, None) => visitor.visit_none(),
+            OwnedField::Optional(_, Some(v)) => visitor.visit_some(self.contained(v)),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> BtResult<V::Value> {
+        match self.first_label() {
+            Some(label) => visitor.visit_borrowed_str(label),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> BtResult<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> BtResult<V::Value> {
+        match self.field {
+            OwnedField::Variant(_, _, v) => {
+                let name = v.name().unwrap_or_default();
+                visitor.visit_enum(VariantAccess {
+                    value: self.child(v, name),
+                })
+            }
+            OwnedField::Scalar(_, ScalarField::String(s)) => {
+                visitor.visit_enum(s.as_str().into_deserializer())
+            }
+            _ => match self.first_label() {
+                Some(label) => visitor.visit_enum(label.into_deserializer()),
+                None => self.deserialize_any(visitor),
+            },
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> BtResult<V::Value> {
+        match self.field {
+            OwnedField::Structure(_, members) if members.is_empty() => visitor.visit_unit(),
+            OwnedField::Optional(_, None) => visitor.visit_unit(),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        bytes byte_buf unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Enum access to the selected option of a variant field
+struct VariantAccess<'de> {
+    value: FieldDeserializer<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for VariantAccess<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> BtResult<(S::Value, Self)> {
+        let name: &'de str = self.value.field.name().unwrap_or_default();
+        let variant = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(name))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> BtResult<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> BtResult<S::Value> {
+        seed.deserialize(self.value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> BtResult<V::Value> {
+        de::Deserializer::deserialize_any(self.value, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> BtResult<V::Value> {
+        de::Deserializer::deserialize_any(self.value, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum State {
+        Running,
+        Stopped,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[allow(non_camel_case_types)]
+    enum Value {
+        int_opt(i32),
+        str_opt(String),
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Payload<'a> {
+        msg: &'a str,
+        len: u8,
+        state: State,
+        state_raw: u32,
+        buf: Vec<i16>,
+        value: Value,
+        maybe: Option<bool>,
+        not_in_trace: Option<u64>,
+    }
+
+    fn payload() -> OwnedField {
+        OwnedField::Structure(
+            None,
+            vec![
+                OwnedField::Scalar(Some("msg".to_owned()), ScalarField::String("hi".to_owned())),
+                OwnedField::Scalar(Some("len".to_owned()), ScalarField::UnsignedInteger(2)),
+                OwnedField::Scalar(
+                    Some("state".to_owned()),
+                    ScalarField::UnsignedEnumeration(0, ["Running".to_owned()].into()),
+                ),
+                OwnedField::Scalar(
+                    Some("state_raw".to_owned()),
+                    ScalarField::UnsignedEnumeration(1, ["Stopped".to_owned()].into()),
+                ),
+                OwnedField::Array(
+                    Some("buf".to_owned()),
+                    vec![
+                        OwnedField::Scalar(None, ScalarField::SignedInteger(-1)),
+                        OwnedField::Scalar(None, ScalarField::SignedInteger(2)),
+                    ],
+                ),
+                OwnedField::Variant(
+                    Some("value".to_owned()),
+                    0,
+                    Box::new(OwnedField::Scalar(
+                        Some("int_opt".to_owned()),
+                        ScalarField::SignedInteger(-3),
+                    )),
+                ),
+                OwnedField::Optional(Some("maybe".to_owned()), None),
+            ],
+        )
+    }
+
+    #[test]
+    fn deserialize_struct() {
+        let f = payload();
+        let p: Payload = f.deserialize_into().unwrap();
+        assert_eq!(
+            p,
+            Payload {
+                msg: "hi",
+                len: 2,
+                state: State::Running,
+                state_raw: 1,
+                buf: vec![-1, 2],
+                value: Value::int_opt(-3),
+                maybe: None,
+                not_in_trace: None,
+            }
+        );
+    }
+
+    #[test]
+    fn missing_member_error() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Other {
+            cpu_id: u32,
+        }
+        let f = payload();
+        assert_eq!(
+            f.deserialize_into::<Other>().unwrap_err(),
+            Error::FieldMissing("<anonymous>".to_owned(), "cpu_id")
+        );
+    }
+
+    #[test]
+    fn missing_nested_member_error() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Inner {
+            id: u32,
+        }
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Outer {
+            inner: Vec<Inner>,
+        }
+        let f = OwnedField::Structure(
+            None,
+            vec![OwnedField::Array(
+                Some("inner".to_owned()),
+                vec![OwnedField::Structure(None, Vec::new())],
+            )],
+        );
+        let err = deserialize_scope::<Outer>(&Some(f), "payload").unwrap_err();
+        assert_eq!(err, Error::FieldMissing("payload.inner.0".to_owned(), "id"));
+        assert_eq!(
+            err.to_string(),
+            "Failed to deserialize field. Missing member 'id' in 'payload.inner.0'"
+        );
+    }
+}
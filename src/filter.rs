@@ -0,0 +1,338 @@
+use crate::message_iterator::UpstreamMessageIterators;
+use crate::util::catch_panic;
+use crate::{
+    ffi, BtResult, Message, NextStatus, SelfComponentFilter, SelfMessageIterator, ValueRef,
+};
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+
+/// A custom filter component class.
+///
+/// The message iterator of each output port pulls from one upstream message
+/// iterator per connected input port. Messages are handed to `next` in batches
+/// per input port, they aren't ordered by time across ports: put a `utils.muxer`
+/// filter upstream for that.
+/// Use `filter_plugin_descriptors!` to generate the plugin and component class
+/// descriptors.
+pub trait Filter: Sized + 'static {
+    /// Create the filter, add its input and output ports here
+    fn initialize(
+        component: &mut SelfComponentFilter,
+        params: Option<ValueRef<'_>>,
+    ) -> BtResult<Self>;
+
+    /// Process a batch of messages from the upstream message iterator of the
+    /// input port at index `input_port`.
+    ///
+    /// Messages pushed to `output` are sent downstream in order, the others are dropped.
+    /// New messages are created with `msg_iter`.
+    fn next(
+        &mut self,
+        msg_iter: &mut SelfMessageIterator,
+        input_port: u64,
+        messages: Vec<Message>,
+        output: &mut VecDeque<Message>,
+    ) -> BtResult<()>;
+
+    fn finalize(&mut self) {}
+}
+
+/// Message iterator user data, owned by the message iterator until it's finalized
+#[derive(Default)]
+struct FilterIterState {
+    upstream: UpstreamMessageIterators,
+    output: VecDeque<Message>,
+    ended: bool,
+}
+
+impl FilterIterState {
+    fn from_msg_iter<'a>(msg_iter: &mut SelfMessageIterator) -> Option<&'a mut Self> {
+        let state = msg_iter.get_c_user_data_ptr() as *mut Self;
+        if state.is_null() {
+            None
+        } else {
+            Some(unsafe { &mut (*state) })
+        }
+    }
+
+    fn create_message_iterators(&mut self, msg_iter: &mut SelfMessageIterator) -> BtResult<()> {
+        let mut component: SelfComponentFilter = msg_iter.borrow_component();
+        for index in 0..component.get_input_port_count() {
+            let port = component.borrow_input_port_by_index(index)?;
+            if port.is_connected() {
+                self.upstream
+                    .push(index, msg_iter.create_message_iterator(&port)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill the output queue, returns `End` or `TryAgain` when it's still empty
+    fn fill<F: Filter>(
+        &mut self,
+        filter: &mut F,
+        msg_iter: &mut SelfMessageIterator,
+    ) -> BtResult<NextStatus> {
+        while self.output.is_empty() {
+            if self.ended {
+                return Ok(NextStatus::End);
+            }
+            let (status, batches) = self.upstream.next_messages()?;
+            for (input_port, messages) in batches {
+                filter.next(msg_iter, input_port, messages, &mut self.output)?;
+            }
+            match status {
+                NextStatus::Ok => (),
+                NextStatus::End => self.ended = true,
+                NextStatus::TryAgain => return Ok(NextStatus::TryAgain),
+            }
+        }
+        Ok(NextStatus::Ok)
+    }
+}
+
+fn filter_from_component<'a, F: Filter>(component: &mut SelfComponentFilter) -> Option<&'a mut F> {
+    let filter = component.get_c_user_data_ptr() as *mut F;
+    if filter.is_null() {
+        None
+    } else {
+        Some(unsafe { &mut (*filter) })
+    }
+}
+
+/// Method glue between libbabeltrace2 and a `Filter` implementation,
+/// used by `filter_plugin_descriptors!`
+pub struct FilterMethods<F: Filter>(PhantomData<F>);
+
+impl<F: Filter> FilterMethods<F> {
+    pub extern "C" fn initialize(
+        filter: *mut ffi::bt_self_component_filter,
+        _config: *mut ffi::bt_self_component_filter_configuration,
+        params: *const ffi::bt_value,
+        _initialize_method_data: *mut c_void,
+    ) -> ffi::bt_component_class_initialize_method_status::Type {
+        use ffi::bt_component_class_initialize_method_status::*;
+
+        let mut component = SelfComponentFilter::from_raw(filter);
+        match catch_panic(|| F::initialize(&mut component, ValueRef::from_raw(params))) {
+            Ok(filter) => {
+                component.set_c_user_data_ptr(Box::into_raw(Box::new(filter)) as *mut c_void);
+                BT_COMPONENT_CLASS_INITIALIZE_METHOD_STATUS_OK
+            }
+            Err(e) => {
+                log::error!("Failed to initialize filter. {}", e);
+                e.append_to_current_thread(file!(), line!());
+                BT_COMPONENT_CLASS_INITIALIZE_METHOD_STATUS_ERROR
+            }
+        }
+    }
+
+    pub extern "C" fn finalize(filter: *mut ffi::bt_self_component_filter) {
+        let mut component = SelfComponentFilter::from_raw(filter);
+        let filter = component.get_c_user_data_ptr() as *mut F;
+        if !filter.is_null() {
+            let mut filter = unsafe { Box::from_raw(filter) };
+            if let Err(e) = catch_panic(|| {
+                filter.finalize();
+                Ok(())
+            }) {
+                log::error!("Failed to finalize filter. {}", e);
+            }
+            component.set_c_user_data_ptr(std::ptr::null_mut());
+        }
+    }
+
+    pub extern "C" fn msg_iter_initialize(
+        msg_iter: *mut ffi::bt_self_message_iterator,
+        _config: *mut ffi::bt_self_message_iterator_configuration,
+        _port: *mut ffi::bt_self_component_port_output,
+    ) -> ffi::bt_message_iterator_class_initialize_method_status::Type {
+        use ffi::bt_message_iterator_class_initialize_method_status::*;
+
+        let mut msg_iter = SelfMessageIterator::from_raw(msg_iter);
+        let mut state = Box::<FilterIterState>::default();
+        match state.create_message_iterators(&mut msg_iter) {
+            Ok(()) => {
+                msg_iter.set_c_user_data_ptr(Box::into_raw(state) as *mut c_void);
+                BT_MESSAGE_ITERATOR_CLASS_INITIALIZE_METHOD_STATUS_OK
+            }
+            Err(e) => {
+                log::error!(
+                    "Failed to create the filter's upstream message iterators. {}",
+                    e
+                );
+                e.append_to_current_thread(file!(), line!());
+                BT_MESSAGE_ITERATOR_CLASS_INITIALIZE_METHOD_STATUS_ERROR
+            }
+        }
+    }
+
+    pub extern "C" fn msg_iter_finalize(msg_iter: *mut ffi::bt_self_message_iterator) {
+        let mut msg_iter = SelfMessageIterator::from_raw(msg_iter);
+        let state = msg_iter.get_c_user_data_ptr() as *mut FilterIterState;
+        if !state.is_null() {
+            unsafe { drop(Box::from_raw(state)) };
+            msg_iter.set_c_user_data_ptr(std::ptr::null_mut());
+        }
+    }
+
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub extern "C" fn msg_iter_next(
+        msg_iter: *mut ffi::bt_self_message_iterator,
+        messages: ffi::bt_message_array_const,
+        capacity: u64,
+        count: *mut u64,
+    ) -> ffi::bt_message_iterator_class_next_method_status::Type {
+        use ffi::bt_message_iterator_class_next_method_status::*;
+
+        let mut msg_iter = SelfMessageIterator::from_raw(msg_iter);
+        let state = FilterIterState::from_msg_iter(&mut msg_iter);
+        let filter = filter_from_component::<F>(&mut msg_iter.borrow_component());
+        let (state, filter) = match (state, filter) {
+            (Some(s), Some(f)) => (s, f),
+            _ => {
+                log::error!("Filter message iterator state is NULL");
+                return BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_ERROR;
+            }
+        };
+
+        match catch_panic(|| state.fill(filter, &mut msg_iter)) {
+            Ok(NextStatus::Ok) => {
+                let n = state.output.len().min(capacity as usize);
+                for (idx, msg) in state.output.drain(..n).enumerate() {
+                    unsafe { *messages.add(idx) = msg.into_raw() };
+                }
+                unsafe { *count = n as u64 };
+                BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_OK
+            }
+            Ok(NextStatus::TryAgain) => BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_AGAIN,
+            Ok(NextStatus::End) => BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_END,
+            Err(e) => {
+                log::error!("Filter message iterator failed. {}", e);
+                e.append_to_current_thread(file!(), line!());
+                BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_ERROR
+            }
+        }
+    }
+}
+
+/// Generate the plugin and filter component class descriptors for a `Filter`
+/// implementation, in a module named `$module`.
+///
+/// The plugin is registered by passing the module to `register_static_plugins!`.
+#[macro_export]
+macro_rules! filter_plugin_descriptors {
+    ($module:ident, $plugin_name:literal, $filter_name:literal, $filter:ty) => {
+        pub mod $module {
+            use super::*;
+            use $crate::ffi::*;
+
+            pub const PLUGIN_NAME: &[u8] = concat!($plugin_name, "\0").as_bytes();
+            pub const FILTER_COMP_NAME: &[u8] = concat!($filter_name, "\0").as_bytes();
+
+            pub static PLUGIN_DESC: __bt_plugin_descriptor = __bt_plugin_descriptor {
+                name: PLUGIN_NAME.as_ptr() as *const _,
+            };
+
+            pub static FILTER_COMP_DESC: __bt_plugin_component_class_descriptor =
+                __bt_plugin_component_class_descriptor {
+                    plugin_descriptor: &PLUGIN_DESC,
+                    name: FILTER_COMP_NAME.as_ptr() as *const _,
+                    type_: bt_component_class_type::BT_COMPONENT_CLASS_TYPE_FILTER,
+                    methods: __bt_plugin_component_class_descriptor__bindgen_ty_1 {
+                        filter: __bt_plugin_component_class_descriptor__bindgen_ty_1__bindgen_ty_2 {
+                            msg_iter_next: Some($crate::FilterMethods::<$filter>::msg_iter_next),
+                        },
+                    },
+                };
+
+            pub static FILTER_COMP_CLASS_INIT_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &FILTER_COMP_DESC,
+                    type_name: b"filter_initialize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_INITIALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        filter_initialize_method: Some($crate::FilterMethods::<$filter>::initialize),
+                    },
+                };
+
+            pub static FILTER_COMP_CLASS_FINI_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &FILTER_COMP_DESC,
+                    type_name: b"filter_finalize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_FINALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        filter_finalize_method: Some($crate::FilterMethods::<$filter>::finalize),
+                    },
+                };
+
+            pub static FILTER_COMP_CLASS_MSG_ITER_INIT_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &FILTER_COMP_DESC,
+                    type_name: b"msg_iter_initialize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_MSG_ITER_INITIALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        msg_iter_initialize_method: Some($crate::FilterMethods::<$filter>::msg_iter_initialize),
+                    },
+                };
+
+            pub static FILTER_COMP_CLASS_MSG_ITER_FINI_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &FILTER_COMP_DESC,
+                    type_name: b"msg_iter_finalize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_MSG_ITER_FINALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        msg_iter_finalize_method: Some($crate::FilterMethods::<$filter>::msg_iter_finalize),
+                    },
+                };
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    struct PassthroughFilter;
+
+    impl Filter for PassthroughFilter {
+        fn initialize(_: &mut SelfComponentFilter, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+            Ok(PassthroughFilter)
+        }
+
+        fn next(
+            &mut self,
+            _: &mut SelfMessageIterator,
+            _: u64,
+            messages: Vec<Message>,
+            output: &mut VecDeque<Message>,
+        ) -> BtResult<()> {
+            output.extend(messages);
+            Ok(())
+        }
+    }
+
+    #[allow(dead_code)]
+    mod descriptors {
+        use super::*;
+        crate::filter_plugin_descriptors!(
+            passthrough_filter_descriptors,
+            "passthrough",
+            "filter",
+            PassthroughFilter
+        );
+    }
+    use descriptors::passthrough_filter_descriptors;
+
+    #[test]
+    fn cstrings_are_valid() {
+        let plugin =
+            CStr::from_bytes_with_nul(passthrough_filter_descriptors::PLUGIN_NAME).unwrap();
+        let filter =
+            CStr::from_bytes_with_nul(passthrough_filter_descriptors::FILTER_COMP_NAME).unwrap();
+        assert_eq!(plugin.to_str().unwrap(), "passthrough");
+        assert_eq!(filter.to_str().unwrap(), "filter");
+    }
+}
@@ -28,7 +28,7 @@ impl Graph {
         let mip_version = 0;
         let inner = unsafe { ffi::bt_graph_create(mip_version) };
         if inner.is_null() {
-            Err(Error::Memory)
+            Err(Error::Memory.with_current_thread_causes())
         } else {
             Ok(Graph { inner })
         }
@@ -36,9 +36,9 @@ impl Graph {
 
     pub fn add_source_component(
         &mut self,
-        class: &ComponentClassSource,
+        class: &ComponentClassSource<'_>,
         name: &CStr,
-        params: &Value,
+        params: Option<&Value>,
         log_level: LoggingLevel,
     ) -> BtResult<ComponentSource> {
         log::debug!("Adding source component to graph");
@@ -48,19 +48,24 @@ impl Graph {
                 self.inner,
                 class.inner,
                 name.as_ptr(),
-                params.inner,
+                params.map_or(ptr::null(), |p| p.inner as *const _),
                 log_level.into(),
                 &mut comp,
             )
         }
-        .capi_result()?;
+        .capi_result()
+        .map_err(Error::with_current_thread_causes)?;
+        if let Some(params) = params {
+            params.mark_frozen();
+        }
         Ok(ComponentSource { inner: comp })
     }
 
     pub fn add_filter_component(
         &mut self,
-        class: &ComponentClassFilter,
+        class: &ComponentClassFilter<'_>,
         name: &CStr,
+        params: Option<&Value>,
         log_level: LoggingLevel,
     ) -> BtResult<ComponentFilter> {
         log::debug!("Adding filter component to graph");
@@ -70,19 +75,50 @@ impl Graph {
                 self.inner,
                 class.inner,
                 name.as_ptr(),
-                ptr::null(),
+                params.map_or(ptr::null(), |p| p.inner as *const _),
                 log_level.into(),
                 &mut comp,
             )
         }
-        .capi_result()?;
+        .capi_result()
+        .map_err(Error::with_current_thread_causes)?;
+        if let Some(params) = params {
+            params.mark_frozen();
+        }
         Ok(ComponentFilter { inner: comp })
     }
 
+    pub fn add_sink_component(
+        &mut self,
+        class: &ComponentClassSink<'_>,
+        name: &CStr,
+        params: Option<&Value>,
+        log_level: LoggingLevel,
+    ) -> BtResult<ComponentSink> {
+        log::debug!("Adding sink component to graph");
+        let mut comp = ptr::null();
+        unsafe {
+            ffi::bt_graph_add_sink_component(
+                self.inner,
+                class.inner,
+                name.as_ptr(),
+                params.map_or(ptr::null(), |p| p.inner as *const _),
+                log_level.into(),
+                &mut comp,
+            )
+        }
+        .capi_result()
+        .map_err(Error::with_current_thread_causes)?;
+        if let Some(params) = params {
+            params.mark_frozen();
+        }
+        Ok(ComponentSink { inner: comp })
+    }
+
     #[allow(clippy::not_unsafe_ptr_arg_deref)]
     pub fn add_sink_component_with_initialize_method_data(
         &mut self,
-        class: &ComponentClassSink,
+        class: &ComponentClassSink<'_>,
         name: &CStr,
         initialize_method_data: *mut c_void,
         log_level: LoggingLevel,
@@ -100,7 +136,8 @@ impl Graph {
                 &mut comp,
             )
         }
-        .capi_result()?;
+        .capi_result()
+        .map_err(Error::with_current_thread_causes)?;
         Ok(ComponentSink { inner: comp })
     }
 
@@ -118,6 +155,7 @@ impl Graph {
             )
         }
         .capi_result()
+        .map_err(Error::with_current_thread_causes)
     }
 
     pub fn run_once(&mut self) -> BtResult<RunStatus> {
@@ -127,7 +165,7 @@ impl Graph {
             BT_GRAPH_RUN_ONCE_STATUS_OK => Ok(RunStatus::Ok),
             BT_GRAPH_RUN_ONCE_STATUS_END => Ok(RunStatus::End),
             BT_GRAPH_RUN_ONCE_STATUS_AGAIN => Ok(RunStatus::TryAgain),
-            _ => Err(Error::Failure(status as _)),
+            _ => Err(Error::Failure(status as _).with_current_thread_causes()),
         }
     }
 }
@@ -6,6 +6,7 @@ use std::path::Path;
 use std::{cmp, fmt, mem, ptr};
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PacketProperties {
     pub packet_total_size_bits: Option<u64>,
     pub packet_content_size_bits: Option<u64>,
@@ -29,7 +30,7 @@ pub struct PacketDecoderConfig {
 impl Default for PacketDecoderConfig {
     fn default() -> Self {
         PacketDecoderConfig {
-            log_level: LoggingLevel::None,
+            log_level: LoggingLevel::default(),
             clock_class_offset_s: 0,
             clock_class_offset_ns: 0,
             force_clock_class_origin_unix_epoch: false,
@@ -82,10 +83,12 @@ impl PacketDecoder {
                 &mut comp,
             )
         };
-        comp_status.capi_result()?;
+        comp_status
+            .capi_result()
+            .map_err(Error::with_current_thread_causes)?;
         if comp.is_null() {
             log::error!("Could not forge a new bt_component from source component class");
-            return Err(Error::Memory);
+            return Err(Error::Memory.with_current_thread_causes());
         }
 
         let md_cfg = ffi::ctf_metadata_decoder_config {
@@ -100,19 +103,21 @@ impl PacketDecoder {
 
         let md_dec = unsafe { ffi::ctf_metadata_decoder_create(&md_cfg) };
         if md_dec.is_null() {
-            return Err(Error::CtfMetadataDecoderCreate);
+            return Err(Error::CtfMetadataDecoderCreate.with_current_thread_causes());
         }
 
         // Process the metadata content
         let md_status = unsafe { ffi::ctf_metadata_decoder_append_content(md_dec, md_file) };
         if md_status != ffi::ctf_metadata_decoder_status::CTF_METADATA_DECODER_STATUS_OK {
-            return Err(Error::CtfMetadataDecoderStatus(md_status as _));
+            return Err(
+                Error::CtfMetadataDecoderStatus(md_status as _).with_current_thread_causes()
+            );
         }
 
         let tc = unsafe { ffi::ctf_metadata_decoder_get_ir_trace_class(md_dec) };
         if tc.is_null() {
             log::error!("Could not get CTF metadata decoder IR trace class");
-            return Err(Error::Memory);
+            return Err(Error::Memory.with_current_thread_causes());
         }
 
         let ctf_tc = unsafe { ffi::ctf_metadata_decoder_borrow_ctf_trace_class(md_dec) };
@@ -127,7 +132,7 @@ impl PacketDecoder {
         let trace = unsafe { ffi::bt_trace_create(tc) };
         if trace.is_null() {
             log::error!("Could not create a trace instance using the CTF metadata trace class");
-            return Err(Error::Memory);
+            return Err(Error::Memory.with_current_thread_causes());
         }
 
         let msg_iter_med_opts = ffi::ctf_msg_iter_medium_ops {
@@ -151,7 +156,7 @@ impl PacketDecoder {
             )
         };
         if msg_iter.is_null() {
-            return Err(Error::CtfMessageIterCreate);
+            return Err(Error::CtfMessageIterCreate.with_current_thread_causes());
         }
 
         // Don't allocate objects since we're just parsing packet header contents
@@ -185,7 +190,7 @@ impl PacketDecoder {
             ffi::ctf_msg_iter_status::CTF_MSG_ITER_STATUS_OK => Ok(Some(props.into())),
             ffi::ctf_msg_iter_status::CTF_MSG_ITER_STATUS_EOF
             | ffi::ctf_msg_iter_status::CTF_MSG_ITER_STATUS_AGAIN => Ok(None),
-            _ => Err(Error::Failure(status as _)),
+            _ => Err(Error::Failure(status as _).with_current_thread_causes()),
         }
     }
 }
@@ -264,7 +269,6 @@ impl Drop for BoxedRawMsgIterState {
             ffi::bt_trace_put_ref(self.as_ref().trace);
             debug_assert!(!self.as_ref().comp.is_null());
             ffi::bt_component_put_ref(self.as_ref().comp);
-            ffi::bt_current_thread_clear_error();
             drop(Box::from_raw(self.0));
         }
     }
@@ -14,23 +14,37 @@ pub(crate) mod util;
 mod clock;
 mod component;
 mod component_class;
+mod ctf_fs_writer;
 mod ctf_iterator;
+mod ctf_message_iterator;
 mod ctf_plugin;
 mod ctf_stream;
+mod discarded;
 mod env;
 mod error;
+mod error_cause;
 mod event;
 mod field;
+mod field_class;
+#[cfg(feature = "serde")]
+mod field_deserializer;
+mod filter;
 mod graph;
 mod logger;
 mod message;
 mod message_iterator;
+mod pipeline;
 mod plugin;
 mod port;
 mod proxy_plugin;
 mod self_component;
+mod self_message_iterator;
+mod sink;
+mod source;
+mod static_plugins;
 mod stream;
 mod trace;
+mod trace_class;
 mod utils_plugin;
 mod value;
 
@@ -39,22 +53,35 @@ pub mod internal_api;
 pub use clock::*;
 pub use component::*;
 pub use component_class::*;
+pub use ctf_fs_writer::*;
 pub use ctf_iterator::*;
+pub use ctf_message_iterator::*;
 pub use ctf_plugin::*;
 pub use ctf_stream::*;
+pub use discarded::*;
 pub use env::*;
 pub use error::*;
+pub use error_cause::*;
 pub use event::*;
 pub use field::*;
+pub use field_class::*;
+#[cfg(feature = "serde")]
+pub use field_deserializer::*;
+pub use filter::*;
 pub use graph::*;
 pub use logger::*;
 pub use message::*;
 pub use message_iterator::*;
+pub use pipeline::*;
 pub use plugin::*;
 pub use port::*;
 pub use proxy_plugin::*;
 pub use self_component::*;
+pub use self_message_iterator::*;
+pub use sink::*;
+pub use source::*;
 pub use stream::*;
 pub use trace::*;
+pub use trace_class::*;
 pub use utils_plugin::*;
 pub use value::*;
@@ -1,8 +1,12 @@
 use crate::ffi;
+use std::ffi::{c_void, CStr};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Once;
+use std::{ptr, slice};
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+/// The libbabeltrace2 logging level, defaults to the `log` crate's `log::max_level`
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum LoggingLevel {
     Trace,
     Debug,
@@ -10,16 +14,144 @@ pub enum LoggingLevel {
     Warn,
     Error,
     Fatal,
-    #[default]
     None,
 }
 
+/// Logging levels of the pipeline behind `CtfIterator` and friends.
+///
+/// Components without their own level use `global`, the source and trimmer
+/// levels are set with their init params' `ComponentLogLevel::with_log_level`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PipelineLoggingLevels {
+    /// The libbabeltrace2 global logging level
+    pub global: LoggingLevel,
+    /// filter.utils.muxer
+    pub muxer: Option<LoggingLevel>,
+    /// The proxy sink funneling messages to the caller
+    pub proxy_sink: Option<LoggingLevel>,
+}
+
+impl From<LoggingLevel> for PipelineLoggingLevels {
+    fn from(global: LoggingLevel) -> Self {
+        PipelineLoggingLevels {
+            global,
+            muxer: None,
+            proxy_sink: None,
+        }
+    }
+}
+
+/// The logging level each component of the pipeline behind `CtfIterator`
+/// was created with, see `CtfIterator::component_logging_levels`
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ComponentLoggingLevels {
+    /// The CTF source
+    pub source: LoggingLevel,
+    /// filter.utils.muxer
+    pub muxer: LoggingLevel,
+    /// filter.utils.trimmer, when the iterator is trimmed
+    pub trimmer: Option<LoggingLevel>,
+    /// The proxy sink funneling messages to the caller
+    pub proxy_sink: LoggingLevel,
+}
+
+/// Init params of a component that can have its own logging level
+pub trait ComponentLogLevel: Sized {
+    /// The component's logging level, `None` uses the pipeline's level
+    fn log_level(&self) -> Option<LoggingLevel>;
+
+    fn set_log_level(&mut self, log_level: Option<LoggingLevel>);
+
+    /// Logging level of the component, defaults to the pipeline's level
+    fn with_log_level(mut self, log_level: LoggingLevel) -> Self {
+        self.set_log_level(Some(log_level));
+        self
+    }
+}
+
 pub struct Logger;
 
 impl Logger {
+    /// Set the libbabeltrace2 global logging level, its log records are
+    /// routed through the `log` crate
     pub fn set_level(level: LoggingLevel) {
+        Self::init_output();
         unsafe { ffi::bt_logging_set_global_level(level.into()) };
     }
+
+    /// Route libbabeltrace2's log records through the `log` crate instead of
+    /// writing them to stderr, the target is `babeltrace2::<tag>`.
+    ///
+    /// Called by `set_level`, only the first call has an effect.
+    pub fn init_output() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| unsafe {
+            ffi::bt_log_set_output_v(
+                ffi::BT_LOG_PUT_TAG | ffi::BT_LOG_PUT_MSG,
+                ptr::null_mut(),
+                Some(log_output),
+            )
+        });
+    }
+}
+
+extern "C" fn log_output(msg: *const ffi::bt_log_message, _arg: *mut c_void) {
+    use ffi::bt_logging_level::*;
+
+    if msg.is_null() {
+        return;
+    }
+    let msg = unsafe { &*msg };
+    let level = match msg.lvl as ffi::bt_logging_level::Type {
+        BT_LOGGING_LEVEL_TRACE => log::Level::Trace,
+        BT_LOGGING_LEVEL_DEBUG => log::Level::Debug,
+        BT_LOGGING_LEVEL_INFO => log::Level::Info,
+        BT_LOGGING_LEVEL_WARNING => log::Level::Warn,
+        BT_LOGGING_LEVEL_ERROR | BT_LOGGING_LEVEL_FATAL => log::Level::Error,
+        _ => return,
+    };
+    if level > log::max_level() {
+        return;
+    }
+
+    let tag = if msg.tag.is_null() {
+        "unknown".into()
+    } else {
+        unsafe { CStr::from_ptr(msg.tag) }.to_string_lossy()
+    };
+    let text = if msg.msg_b.is_null() || msg.p < msg.msg_b {
+        "".into()
+    } else {
+        let len = msg.p as usize - msg.msg_b as usize;
+        let bytes = unsafe { slice::from_raw_parts(msg.msg_b as *const u8, len) };
+        String::from_utf8_lossy(bytes)
+    };
+    log::log!(
+        target: &format!("babeltrace2::{}", tag),
+        level,
+        "{}",
+        text.trim_end()
+    );
+}
+
+impl Default for LoggingLevel {
+    fn default() -> Self {
+        log::max_level().into()
+    }
+}
+
+impl From<log::LevelFilter> for LoggingLevel {
+    fn from(level: log::LevelFilter) -> Self {
+        use log::LevelFilter;
+        match level {
+            LevelFilter::Off => LoggingLevel::None,
+            LevelFilter::Error => LoggingLevel::Error,
+            LevelFilter::Warn => LoggingLevel::Warn,
+            LevelFilter::Info => LoggingLevel::Info,
+            LevelFilter::Debug => LoggingLevel::Debug,
+            LevelFilter::Trace => LoggingLevel::Trace,
+        }
+    }
 }
 
 impl From<LoggingLevel> for ffi::bt_logging_level::Type {
@@ -37,6 +169,21 @@ impl From<LoggingLevel> for ffi::bt_logging_level::Type {
     }
 }
 
+impl From<ffi::bt_logging_level::Type> for LoggingLevel {
+    fn from(level: ffi::bt_logging_level::Type) -> Self {
+        use ffi::bt_logging_level::*;
+        match level {
+            BT_LOGGING_LEVEL_TRACE => LoggingLevel::Trace,
+            BT_LOGGING_LEVEL_DEBUG => LoggingLevel::Debug,
+            BT_LOGGING_LEVEL_INFO => LoggingLevel::Info,
+            BT_LOGGING_LEVEL_WARNING => LoggingLevel::Warn,
+            BT_LOGGING_LEVEL_ERROR => LoggingLevel::Error,
+            BT_LOGGING_LEVEL_FATAL => LoggingLevel::Fatal,
+            _ => LoggingLevel::None,
+        }
+    }
+}
+
 impl fmt::Display for LoggingLevel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use LoggingLevel::*;
@@ -70,3 +217,33 @@ impl FromStr for LoggingLevel {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_log_level_filter() {
+        assert_eq!(
+            LoggingLevel::from(log::LevelFilter::Off),
+            LoggingLevel::None
+        );
+        assert_eq!(
+            LoggingLevel::from(log::LevelFilter::Warn),
+            LoggingLevel::Warn
+        );
+        assert_eq!(
+            LoggingLevel::from(log::LevelFilter::Trace),
+            LoggingLevel::Trace
+        );
+    }
+
+    #[test]
+    fn ffi_logging_level_round_trip() {
+        use LoggingLevel::*;
+        for level in [Trace, Debug, Info, Warn, Error, Fatal, None] {
+            let raw: ffi::bt_logging_level::Type = level.into();
+            assert_eq!(LoggingLevel::from(raw), level);
+        }
+    }
+}
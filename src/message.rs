@@ -1,7 +1,20 @@
-use crate::{ffi, BtResult, ClockSnapshot, Error, Event, Stream};
+use crate::{
+    ffi, BtResult, ClockNanoseconds, ClockSnapshot, Discarded, DiscardedKind, Error, Event,
+    EventRef, Field, OwnedEvent, OwnedField, Packet, Stream, StreamId, StreamProperties, Trace,
+    TraceIndex,
+};
 use std::os::raw::c_uint;
 use std::{ptr, slice};
 
+/// The field values of an event created with `SelfMessageIterator::create_event_message`,
+/// a scope is left to its default value when `None`
+#[derive(Copy, Clone, Default, Debug)]
+pub struct EventFields<'a> {
+    pub common_context: Option<&'a OwnedField>,
+    pub specific_context: Option<&'a OwnedField>,
+    pub payload: Option<&'a OwnedField>,
+}
+
 pub struct MessageArray {
     pub(crate) count: u64,
     pub(crate) inner: ffi::bt_message_array_const,
@@ -33,6 +46,13 @@ impl Message {
         Message { inner: message }
     }
 
+    /// Give up ownership of the message, e.g. to a message iterator's output array
+    pub(crate) fn into_raw(self) -> *const ffi::bt_message {
+        let inner = self.inner;
+        std::mem::forget(self);
+        inner
+    }
+
     pub fn get_type(&self) -> MessageType {
         use ffi::bt_message_type::*;
         use MessageType::*;
@@ -80,6 +100,273 @@ impl Message {
             })
         }
     }
+
+    /// Set the fields of a newly created event message, before it's emitted.
+    ///
+    /// Each value is written to the event's corresponding scope when present.
+    pub(crate) fn set_event_fields(&mut self, fields: &EventFields<'_>) -> BtResult<()> {
+        let typ = self.get_type();
+        if typ != MessageType::Event {
+            return Err(Error::NotAnEventMessage(typ));
+        }
+        let event = unsafe { ffi::bt_message_event_borrow_event(self.inner as *mut _) };
+        if event.is_null() {
+            return Err(Error::ResourceBorrow);
+        }
+        let scopes = [
+            (fields.common_context, unsafe {
+                ffi::bt_event_borrow_common_context_field(event)
+            }),
+            (fields.specific_context, unsafe {
+                ffi::bt_event_borrow_specific_context_field(event)
+            }),
+            (fields.payload, unsafe {
+                ffi::bt_event_borrow_payload_field(event)
+            }),
+        ];
+        for (value, field) in scopes.iter() {
+            if let Some(value) = value {
+                if field.is_null() {
+                    return Err(Error::ResourceBorrow);
+                }
+                value.write_to(*field)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Borrow the trace of the message's stream, if the message has one
+    pub(crate) fn borrow_trace(&self) -> BtResult<Option<Trace>> {
+        match self.borrow_stream()? {
+            Some(stream) => stream.trace().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Borrow the message's stream, if the message has one
+    pub fn borrow_stream(&self) -> BtResult<Option<Stream>> {
+        let msg = self.inner;
+        let stream = unsafe {
+            match self.get_type() {
+                MessageType::StreamBeginning => {
+                    ffi::bt_message_stream_beginning_borrow_stream_const(msg)
+                }
+                MessageType::StreamEnd => ffi::bt_message_stream_end_borrow_stream_const(msg),
+                MessageType::Event => {
+                    ffi::bt_event_borrow_stream_const(ffi::bt_message_event_borrow_event_const(msg))
+                }
+                MessageType::PacketBeginning => ffi::bt_packet_borrow_stream_const(
+                    ffi::bt_message_packet_beginning_borrow_packet_const(msg),
+                ),
+                MessageType::PacketEnd => ffi::bt_packet_borrow_stream_const(
+                    ffi::bt_message_packet_end_borrow_packet_const(msg),
+                ),
+                MessageType::DiscardedEvents => {
+                    ffi::bt_message_discarded_events_borrow_stream_const(msg)
+                }
+                MessageType::DiscardedPackets => {
+                    ffi::bt_message_discarded_packets_borrow_stream_const(msg)
+                }
+                MessageType::MessageIteratorInactivity | MessageType::Unknown(_) => {
+                    return Ok(None)
+                }
+            }
+        };
+        if stream.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(Some(Stream { inner: stream }))
+        }
+    }
+
+    /// Borrow the message's packet, if the message is an event or a packet
+    /// message and its stream supports packets
+    pub fn borrow_packet(&self) -> Option<Packet> {
+        let msg = self.inner;
+        let packet = unsafe {
+            match self.get_type() {
+                MessageType::Event => {
+                    ffi::bt_event_borrow_packet_const(ffi::bt_message_event_borrow_event_const(msg))
+                }
+                MessageType::PacketBeginning => {
+                    ffi::bt_message_packet_beginning_borrow_packet_const(msg)
+                }
+                MessageType::PacketEnd => ffi::bt_message_packet_end_borrow_packet_const(msg),
+                _ => return None,
+            }
+        };
+        if packet.is_null() {
+            None
+        } else {
+            Some(Packet { inner: packet })
+        }
+    }
+
+    /// Convert a discarded events or discarded packets message
+    pub(crate) fn to_discarded(&self) -> BtResult<Discarded> {
+        use ffi::bt_property_availability::*;
+        let (kind, stream, count_avail, count, begin, end) = match self.get_type() {
+            MessageType::DiscardedEvents => unsafe {
+                let stream = ffi::bt_message_discarded_events_borrow_stream_const(self.inner);
+                let class = ffi::bt_stream_borrow_class_const(stream);
+                let mut count = 0;
+                let avail = ffi::bt_message_discarded_events_get_count(self.inner, &mut count);
+                let (begin, end) =
+                    if ffi::bt_stream_class_discarded_events_have_default_clock_snapshots(class)
+                        != 0
+                    {
+                        (
+                            ffi::bt_message_discarded_events_borrow_beginning_default_clock_snapshot_const(self.inner),
+                            ffi::bt_message_discarded_events_borrow_end_default_clock_snapshot_const(self.inner),
+                        )
+                    } else {
+                        (ptr::null(), ptr::null())
+                    };
+                (DiscardedKind::Events, stream, avail, count, begin, end)
+            },
+            MessageType::DiscardedPackets => unsafe {
+                let stream = ffi::bt_message_discarded_packets_borrow_stream_const(self.inner);
+                let class = ffi::bt_stream_borrow_class_const(stream);
+                let mut count = 0;
+                let avail = ffi::bt_message_discarded_packets_get_count(self.inner, &mut count);
+                let (begin, end) =
+                    if ffi::bt_stream_class_discarded_packets_have_default_clock_snapshots(class)
+                        != 0
+                    {
+                        (
+                            ffi::bt_message_discarded_packets_borrow_beginning_default_clock_snapshot_const(self.inner),
+                            ffi::bt_message_discarded_packets_borrow_end_default_clock_snapshot_const(self.inner),
+                        )
+                    } else {
+                        (ptr::null(), ptr::null())
+                    };
+                (DiscardedKind::Packets, stream, avail, count, begin, end)
+            },
+            _ => return Err(Error::ResourceBorrow),
+        };
+        if stream.is_null() {
+            return Err(Error::ResourceBorrow);
+        }
+        Ok(Discarded {
+            kind,
+            trace_index: 0,
+            stream_id: unsafe { ffi::bt_stream_get_id(stream) },
+            count: if count_avail == BT_PROPERTY_AVAILABILITY_AVAILABLE {
+                Some(count)
+            } else {
+                None
+            },
+            beginning_clock_snapshot: snapshot_ns(begin),
+            end_clock_snapshot: snapshot_ns(end),
+        })
+    }
+
+    /// Convert any message into an owned message
+    pub(crate) fn to_owned_message(&self) -> BtResult<OwnedMessage> {
+        use ffi::bt_message_stream_clock_snapshot_state::*;
+        let msg = self.inner;
+        Ok(match self.get_type() {
+            MessageType::StreamBeginning => {
+                let stream = self.stream_beginning_borrow_stream()?;
+                let mut clock = ptr::null();
+                if has_default_clock(stream.inner)
+                    && unsafe {
+                        ffi::bt_message_stream_beginning_borrow_default_clock_snapshot_const(
+                            msg, &mut clock,
+                        )
+                    } != BT_MESSAGE_STREAM_CLOCK_SNAPSHOT_STATE_KNOWN
+                {
+                    clock = ptr::null();
+                }
+                OwnedMessage::StreamBeginning(StreamMessage {
+                    properties: stream.properties()?,
+                    clock_snapshot: snapshot_ns(clock),
+                })
+            }
+            MessageType::StreamEnd => {
+                let stream = unsafe { ffi::bt_message_stream_end_borrow_stream_const(msg) };
+                if stream.is_null() {
+                    return Err(Error::ResourceBorrow);
+                }
+                let mut clock = ptr::null();
+                if has_default_clock(stream)
+                    && unsafe {
+                        ffi::bt_message_stream_end_borrow_default_clock_snapshot_const(
+                            msg, &mut clock,
+                        )
+                    } != BT_MESSAGE_STREAM_CLOCK_SNAPSHOT_STATE_KNOWN
+                {
+                    clock = ptr::null();
+                }
+                OwnedMessage::StreamEnd(StreamMessage {
+                    properties: Stream { inner: stream }.properties()?,
+                    clock_snapshot: snapshot_ns(clock),
+                })
+            }
+            MessageType::Event => OwnedMessage::Event(self.borrow_event()?.to_owned()?),
+            MessageType::PacketBeginning => {
+                let packet = unsafe { ffi::bt_message_packet_beginning_borrow_packet_const(msg) };
+                let clock = if packet_has_clock(packet, true) {
+                    unsafe {
+                        ffi::bt_message_packet_beginning_borrow_default_clock_snapshot_const(msg)
+                    }
+                } else {
+                    ptr::null()
+                };
+                OwnedMessage::PacketBeginning(PacketMessage::new(packet, clock)?)
+            }
+            MessageType::PacketEnd => {
+                let packet = unsafe { ffi::bt_message_packet_end_borrow_packet_const(msg) };
+                let clock = if packet_has_clock(packet, false) {
+                    unsafe { ffi::bt_message_packet_end_borrow_default_clock_snapshot_const(msg) }
+                } else {
+                    ptr::null()
+                };
+                OwnedMessage::PacketEnd(PacketMessage::new(packet, clock)?)
+            }
+            MessageType::DiscardedEvents => OwnedMessage::DiscardedEvents(self.to_discarded()?),
+            MessageType::DiscardedPackets => OwnedMessage::DiscardedPackets(self.to_discarded()?),
+            MessageType::MessageIteratorInactivity => {
+                OwnedMessage::MessageIteratorInactivity(snapshot_ns(unsafe {
+                    ffi::bt_message_message_iterator_inactivity_borrow_clock_snapshot_const(msg)
+                }))
+            }
+            MessageType::Unknown(typ) => OwnedMessage::Unknown(typ),
+        })
+    }
+
+    /// Borrow the event in place, it can't outlive this message
+    pub fn borrow_event_ref(&self) -> BtResult<EventRef<'_>> {
+        self.borrow_event().map(EventRef::new)
+    }
+}
+
+fn snapshot_ns(clock: *const ffi::bt_clock_snapshot) -> Option<ClockNanoseconds> {
+    if clock.is_null() {
+        None
+    } else {
+        ClockSnapshot { inner: clock }.ns_from_origin()
+    }
+}
+
+fn has_default_clock(stream: *const ffi::bt_stream) -> bool {
+    let class = unsafe { ffi::bt_stream_borrow_class_const(stream) };
+    !unsafe { ffi::bt_stream_class_borrow_default_clock_class_const(class) }.is_null()
+}
+
+fn packet_has_clock(packet: *const ffi::bt_packet, beginning: bool) -> bool {
+    if packet.is_null() {
+        return false;
+    }
+    unsafe {
+        let stream = ffi::bt_packet_borrow_stream_const(packet);
+        let class = ffi::bt_stream_borrow_class_const(stream);
+        if beginning {
+            ffi::bt_stream_class_packets_have_beginning_default_clock_snapshot(class) != 0
+        } else {
+            ffi::bt_stream_class_packets_have_end_default_clock_snapshot(class) != 0
+        }
+    }
 }
 
 impl Drop for Message {
@@ -89,6 +376,7 @@ impl Drop for Message {
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MessageType {
     StreamBeginning,
     StreamEnd,
@@ -100,3 +388,90 @@ pub enum MessageType {
     MessageIteratorInactivity,
     Unknown(c_uint),
 }
+
+/// Owned version of a message, of any type
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedMessage {
+    StreamBeginning(StreamMessage),
+    StreamEnd(StreamMessage),
+    Event(OwnedEvent),
+    PacketBeginning(PacketMessage),
+    PacketEnd(PacketMessage),
+    DiscardedEvents(Discarded),
+    DiscardedPackets(Discarded),
+    /// Clock snapshot of the inactivity
+    MessageIteratorInactivity(Option<ClockNanoseconds>),
+    Unknown(c_uint),
+}
+
+impl OwnedMessage {
+    pub(crate) fn set_trace_index(&mut self, trace_index: TraceIndex) {
+        match self {
+            OwnedMessage::StreamBeginning(m) | OwnedMessage::StreamEnd(m) => {
+                m.properties.trace_index = trace_index
+            }
+            OwnedMessage::Event(e) => e.trace_index = trace_index,
+            OwnedMessage::PacketBeginning(p) | OwnedMessage::PacketEnd(p) => {
+                p.trace_index = trace_index
+            }
+            OwnedMessage::DiscardedEvents(d) | OwnedMessage::DiscardedPackets(d) => {
+                d.trace_index = trace_index
+            }
+            OwnedMessage::MessageIteratorInactivity(_) | OwnedMessage::Unknown(_) => (),
+        }
+    }
+
+    pub fn get_type(&self) -> MessageType {
+        match self {
+            OwnedMessage::StreamBeginning(_) => MessageType::StreamBeginning,
+            OwnedMessage::StreamEnd(_) => MessageType::StreamEnd,
+            OwnedMessage::Event(_) => MessageType::Event,
+            OwnedMessage::PacketBeginning(_) => MessageType::PacketBeginning,
+            OwnedMessage::PacketEnd(_) => MessageType::PacketEnd,
+            OwnedMessage::DiscardedEvents(_) => MessageType::DiscardedEvents,
+            OwnedMessage::DiscardedPackets(_) => MessageType::DiscardedPackets,
+            OwnedMessage::MessageIteratorInactivity(_) => MessageType::MessageIteratorInactivity,
+            OwnedMessage::Unknown(t) => MessageType::Unknown(*t),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamMessage {
+    pub properties: StreamProperties,
+    /// Default clock snapshot, if the stream has a default clock and it's known
+    pub clock_snapshot: Option<ClockNanoseconds>,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PacketMessage {
+    /// Index of the trace the stream belongs to.
+    /// Assigned by the iterators, zero when converted on its own.
+    pub trace_index: TraceIndex,
+    pub stream_id: StreamId,
+    /// Default clock snapshot, if the stream class supports it
+    pub clock_snapshot: Option<ClockNanoseconds>,
+    pub context: Option<OwnedField>,
+}
+
+impl PacketMessage {
+    fn new(packet: *const ffi::bt_packet, clock: *const ffi::bt_clock_snapshot) -> BtResult<Self> {
+        if packet.is_null() {
+            return Err(Error::ResourceBorrow);
+        }
+        let stream = unsafe { ffi::bt_packet_borrow_stream_const(packet) };
+        let field = unsafe { ffi::bt_packet_borrow_context_field_const(packet) };
+        Ok(PacketMessage {
+            trace_index: 0,
+            stream_id: unsafe { ffi::bt_stream_get_id(stream) },
+            clock_snapshot: snapshot_ns(clock),
+            context: Field::from_raw(field)
+                .map(|f| f.to_owned())
+                .transpose()?
+                .flatten(),
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{ffi, BtResult, Error, MessageArray};
+use crate::{ffi, BtResult, BtResultExt, ClockNanoseconds, Error, Message, MessageArray};
 use std::ptr;
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -34,6 +34,95 @@ impl MessageIterator {
             _ => Err(Error::Failure(status as _)),
         }
     }
+
+    pub fn can_seek_beginning(&mut self) -> BtResult<bool> {
+        let mut can_seek = 0;
+        let status =
+            unsafe { ffi::bt_message_iterator_can_seek_beginning(self.inner, &mut can_seek) };
+        status.capi_result()?;
+        Ok(can_seek != 0)
+    }
+
+    pub fn seek_beginning(&mut self) -> BtResult<()> {
+        unsafe { ffi::bt_message_iterator_seek_beginning(self.inner) }.capi_result()
+    }
+
+    pub fn can_seek_ns_from_origin(&mut self, ns_from_origin: ClockNanoseconds) -> BtResult<bool> {
+        let mut can_seek = 0;
+        let status = unsafe {
+            ffi::bt_message_iterator_can_seek_ns_from_origin(
+                self.inner,
+                ns_from_origin,
+                &mut can_seek,
+            )
+        };
+        status.capi_result()?;
+        Ok(can_seek != 0)
+    }
+
+    pub fn seek_ns_from_origin(&mut self, ns_from_origin: ClockNanoseconds) -> BtResult<()> {
+        unsafe { ffi::bt_message_iterator_seek_ns_from_origin(self.inner, ns_from_origin) }
+            .capi_result()
+    }
+}
+
+/// A batch of messages and the index of the input port they came from
+pub(crate) type PortMessages = (u64, Vec<Message>);
+
+/// The upstream message iterators of a component, one per connected input port
+#[derive(Default)]
+pub(crate) struct UpstreamMessageIterators {
+    iters: Vec<(u64, MessageIterator)>,
+    ended: Vec<bool>,
+}
+
+impl UpstreamMessageIterators {
+    /// Add the message iterator of the input port at index `input_port`
+    pub(crate) fn push(&mut self, input_port: u64, iter: MessageIterator) {
+        self.iters.push((input_port, iter));
+        self.ended.push(false);
+    }
+
+    /// Pull a batch of messages from each upstream message iterator that hasn't ended,
+    /// along with the index of the input port it belongs to.
+    ///
+    /// The status is `End` when all of them have ended, and `TryAgain` when there
+    /// are no messages and at least one of them said to try again.
+    pub(crate) fn next_messages(&mut self) -> BtResult<(NextStatus, Vec<PortMessages>)> {
+        let mut batches = Vec::new();
+        let mut try_again = false;
+        for ((input_port, iter), ended) in self.iters.iter_mut().zip(self.ended.iter_mut()) {
+            if *ended {
+                continue;
+            }
+            let (status, msg_array) = iter.next_message_array()?;
+            match status {
+                NextStatus::Ok => {
+                    let messages: Vec<Message> = msg_array
+                        .as_slice()
+                        .iter()
+                        .map(|msg| Message::from_raw(*msg))
+                        .collect();
+                    if !messages.is_empty() {
+                        batches.push((*input_port, messages));
+                    }
+                }
+                NextStatus::TryAgain => try_again = true,
+                NextStatus::End => *ended = true,
+            }
+        }
+
+        let status = if !batches.is_empty() {
+            NextStatus::Ok
+        } else if self.ended.iter().all(|e| *e) {
+            NextStatus::End
+        } else if try_again {
+            NextStatus::TryAgain
+        } else {
+            NextStatus::Ok
+        };
+        Ok((status, batches))
+    }
 }
 
 impl Drop for MessageIterator {
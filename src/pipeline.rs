@@ -0,0 +1,391 @@
+use crate::{
+    BtResult, ComponentFilter, ComponentSink, ComponentSource, Error, Graph, InputPort, Logger,
+    LoggingLevel, OutputPort, Plugin, PluginSet, RunStatus, Value,
+};
+use std::collections::{btree_map::Entry, BTreeMap};
+use std::ffi::{CStr, CString};
+use std::fmt;
+
+/// A component added to a pipeline
+pub enum PipelineComponent {
+    Source(ComponentSource),
+    Filter(ComponentFilter),
+    Sink(ComponentSink),
+}
+
+impl PipelineComponent {
+    fn kind(&self) -> &'static str {
+        match self {
+            PipelineComponent::Source(_) => "source",
+            PipelineComponent::Filter(_) => "filter",
+            PipelineComponent::Sink(_) => "sink",
+        }
+    }
+
+    fn input_port_count(&self) -> u64 {
+        match self {
+            PipelineComponent::Source(_) => 0,
+            PipelineComponent::Filter(c) => c.get_input_port_count(),
+            PipelineComponent::Sink(c) => c.get_input_port_count(),
+        }
+    }
+
+    fn output_port_count(&self) -> u64 {
+        match self {
+            PipelineComponent::Source(c) => c.get_output_port_count(),
+            PipelineComponent::Filter(c) => c.get_output_port_count(),
+            PipelineComponent::Sink(_) => 0,
+        }
+    }
+
+    fn input_port(&self, port: &PortSelector) -> BtResult<InputPort> {
+        match (self, port) {
+            (PipelineComponent::Source(_), _) => Err(Error::NoPortsOfKind("source", "input")),
+            (PipelineComponent::Filter(c), PortSelector::Index(i)) => {
+                c.borrow_input_port_by_index(*i)
+            }
+            (PipelineComponent::Filter(c), PortSelector::Name(n)) => {
+                c.borrow_input_port_by_name(&CString::new(n.as_str())?)
+            }
+            (PipelineComponent::Sink(c), PortSelector::Index(i)) => {
+                c.borrow_input_port_by_index(*i)
+            }
+            (PipelineComponent::Sink(c), PortSelector::Name(n)) => {
+                c.borrow_input_port_by_name(&CString::new(n.as_str())?)
+            }
+        }
+    }
+
+    fn output_port(&self, port: &PortSelector) -> BtResult<OutputPort> {
+        match (self, port) {
+            (PipelineComponent::Sink(_), _) => Err(Error::NoPortsOfKind("sink", "output")),
+            (PipelineComponent::Source(c), PortSelector::Index(i)) => {
+                c.borrow_output_port_by_index(*i)
+            }
+            (PipelineComponent::Source(c), PortSelector::Name(n)) => {
+                c.borrow_output_port_by_name(&CString::new(n.as_str())?)
+            }
+            (PipelineComponent::Filter(c), PortSelector::Index(i)) => {
+                c.borrow_output_port_by_index(*i)
+            }
+            (PipelineComponent::Filter(c), PortSelector::Name(n)) => {
+                c.borrow_output_port_by_name(&CString::new(n.as_str())?)
+            }
+        }
+    }
+}
+
+/// Selects a component port by index or by name
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum PortSelector {
+    Index(u64),
+    Name(String),
+}
+
+impl From<u64> for PortSelector {
+    fn from(index: u64) -> Self {
+        PortSelector::Index(index)
+    }
+}
+
+impl From<&str> for PortSelector {
+    fn from(name: &str) -> Self {
+        PortSelector::Name(name.to_owned())
+    }
+}
+
+impl From<String> for PortSelector {
+    fn from(name: String) -> Self {
+        PortSelector::Name(name)
+    }
+}
+
+impl fmt::Display for PortSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortSelector::Index(i) => write!(f, "#{}", i),
+            PortSelector::Name(n) => write!(f, "'{}'", n),
+        }
+    }
+}
+
+/// Builds a trace processing graph out of any component classes
+/// provided by the static plugins, or by the plugin sets added with `add_plugins`.
+/// Components are referred to by their unique name.
+///
+/// ```ignore
+/// let mut builder = PipelineBuilder::new(LoggingLevel::Warn)?;
+/// builder
+///     .add_source("ctf", "fs", "src", Some(&params))?
+///     .add_filter("utils", "muxer", "muxer", None)?
+///     .add_sink("utils", "dummy", "dummy", None)?
+///     .connect_all("src", "muxer")?
+///     .connect("muxer", 0, "dummy", "in")?;
+/// let mut pipeline = builder.build();
+/// pipeline.run()?;
+/// ```
+pub struct PipelineBuilder {
+    log_level: LoggingLevel,
+    component_log_level: Option<LoggingLevel>,
+    graph: Graph,
+    components: BTreeMap<String, PipelineComponent>,
+    plugins: BTreeMap<String, Plugin>,
+}
+
+impl PipelineBuilder {
+    pub fn new(log_level: LoggingLevel) -> BtResult<Self> {
+        Logger::set_level(log_level);
+        Ok(PipelineBuilder {
+            log_level,
+            component_log_level: None,
+            graph: Graph::new()?,
+            components: Default::default(),
+            plugins: Default::default(),
+        })
+    }
+
+    /// Make the plugins of `plugins` available to the components added from now on,
+    /// plugins already available under the same name take precedence
+    pub fn add_plugins(&mut self, plugins: &PluginSet) -> BtResult<&mut Self> {
+        for plugin in plugins.plugins() {
+            let name = plugin.name()?;
+            self.plugins.entry(name).or_insert(plugin);
+        }
+        Ok(self)
+    }
+
+    /// Logging level of the components added from now on, `None` reverts
+    /// to the pipeline's level
+    pub fn set_component_log_level(&mut self, log_level: Option<LoggingLevel>) -> &mut Self {
+        self.component_log_level = log_level;
+        self
+    }
+
+    fn next_component_log_level(&self) -> LoggingLevel {
+        self.component_log_level.unwrap_or(self.log_level)
+    }
+
+    /// Add a source component of the class `source.<plugin>.<class>`
+    pub fn add_source(
+        &mut self,
+        plugin: &str,
+        class: &str,
+        name: &str,
+        params: Option<&Value>,
+    ) -> BtResult<&mut Self> {
+        let class_c = CString::new(class)?;
+        self.add_component(plugin, name, |p, graph, name_c, log_level| {
+            let comp_class = p
+                .borrow_source_component_class_by_name(&class_c)
+                .map_err(|_| {
+                    Error::ComponentClassNotFound(format!("source.{}.{}", plugin, class))
+                })?;
+            let comp = graph.add_source_component(&comp_class, name_c, params, log_level)?;
+            Ok(PipelineComponent::Source(comp))
+        })
+    }
+
+    /// Add a filter component of the class `filter.<plugin>.<class>`
+    pub fn add_filter(
+        &mut self,
+        plugin: &str,
+        class: &str,
+        name: &str,
+        params: Option<&Value>,
+    ) -> BtResult<&mut Self> {
+        let class_c = CString::new(class)?;
+        self.add_component(plugin, name, |p, graph, name_c, log_level| {
+            let comp_class = p
+                .borrow_filter_component_class_by_name(&class_c)
+                .map_err(|_| {
+                    Error::ComponentClassNotFound(format!("filter.{}.{}", plugin, class))
+                })?;
+            let comp = graph.add_filter_component(&comp_class, name_c, params, log_level)?;
+            Ok(PipelineComponent::Filter(comp))
+        })
+    }
+
+    /// Add a sink component of the class `sink.<plugin>.<class>`
+    pub fn add_sink(
+        &mut self,
+        plugin: &str,
+        class: &str,
+        name: &str,
+        params: Option<&Value>,
+    ) -> BtResult<&mut Self> {
+        let class_c = CString::new(class)?;
+        self.add_component(plugin, name, |p, graph, name_c, log_level| {
+            let comp_class = p
+                .borrow_sink_component_class_by_name(&class_c)
+                .map_err(|_| Error::ComponentClassNotFound(format!("sink.{}.{}", plugin, class)))?;
+            let comp = graph.add_sink_component(&comp_class, name_c, params, log_level)?;
+            Ok(PipelineComponent::Sink(comp))
+        })
+    }
+
+    /// Connect an output port of the `upstream` component to an input port
+    /// of the `downstream` component
+    pub fn connect<O: Into<PortSelector>, I: Into<PortSelector>>(
+        &mut self,
+        upstream: &str,
+        output: O,
+        downstream: &str,
+        input: I,
+    ) -> BtResult<&mut Self> {
+        let output = output.into();
+        let input = input.into();
+        let up = self.component_by_name(upstream)?;
+        let down = self.component_by_name(downstream)?;
+        let out_port = up.output_port(&output).map_err(|e| match e {
+            e @ Error::NoPortsOfKind(..) => e,
+            _ => Error::PortNotFound(upstream.to_owned(), up.kind(), "output", output.to_string()),
+        })?;
+        let in_port = down.input_port(&input).map_err(|e| match e {
+            e @ Error::NoPortsOfKind(..) => e,
+            _ => Error::PortNotFound(
+                downstream.to_owned(),
+                down.kind(),
+                "input",
+                input.to_string(),
+            ),
+        })?;
+        log::debug!(
+            "Connecting {} port {} to {} port {}",
+            upstream,
+            output,
+            downstream,
+            input
+        );
+        self.graph.connect_ports(&out_port, &in_port).map_err(|e| {
+            Error::PortConnection(
+                format!("{} port {}", upstream, output),
+                format!("{} port {}", downstream, input),
+                Box::new(e),
+            )
+        })?;
+        Ok(self)
+    }
+
+    /// Connect every output port of the `upstream` component to the next
+    /// available input port of the `downstream` component, e.g. a source to a muxer
+    pub fn connect_all(&mut self, upstream: &str, downstream: &str) -> BtResult<&mut Self> {
+        let num_out_ports = self.component_by_name(upstream)?.output_port_count();
+        for out_idx in 0..num_out_ports {
+            let down = self.component_by_name(downstream)?;
+            let in_idx = (0..down.input_port_count())
+                .find(|idx| {
+                    down.input_port(&PortSelector::Index(*idx))
+                        .map(|p| !p.is_connected())
+                        .unwrap_or(false)
+                })
+                .ok_or_else(|| {
+                    Error::PortNotFound(
+                        downstream.to_owned(),
+                        down.kind(),
+                        "available input",
+                        format!("for {} port #{}", upstream, out_idx),
+                    )
+                })?;
+            self.connect(upstream, out_idx, downstream, in_idx)?;
+        }
+        Ok(self)
+    }
+
+    pub fn component(&self, name: &str) -> Option<&PipelineComponent> {
+        self.components.get(name)
+    }
+
+    pub fn build(self) -> Pipeline {
+        Pipeline {
+            graph: self.graph,
+            components: self.components,
+            _plugins: self.plugins,
+        }
+    }
+
+    /// Validate the component name and find its plugin, `add` borrows the
+    /// component class from the plugin and adds the component to the graph.
+    /// Components without params get an empty map.
+    fn add_component<F>(&mut self, plugin: &str, name: &str, add: F) -> BtResult<&mut Self>
+    where
+        F: FnOnce(&Plugin, &mut Graph, &CStr, LoggingLevel) -> BtResult<PipelineComponent>,
+    {
+        if self.components.contains_key(name) {
+            return Err(Error::DuplicateComponentName(name.to_owned()));
+        }
+        let name_c = CString::new(name)?;
+        let log_level = self.next_component_log_level();
+        let plugin = Self::plugin(&mut self.plugins, plugin)?;
+        let comp = add(plugin, &mut self.graph, &name_c, log_level)?;
+        self.components.insert(name.to_owned(), comp);
+        Ok(self)
+    }
+
+    fn component_by_name(&self, name: &str) -> BtResult<&PipelineComponent> {
+        self.components
+            .get(name)
+            .ok_or_else(|| Error::ComponentNotFound(name.to_owned()))
+    }
+
+    /// Borrows only the plugins so that their component classes can be added
+    /// to the graph. Plugins are loaded from the static plugins on first use,
+    /// a missing plugin is a `StaticPluginNotRegistered` error.
+    fn plugin<'p>(plugins: &'p mut BTreeMap<String, Plugin>, name: &str) -> BtResult<&'p Plugin> {
+        Ok(match plugins.entry(name.to_owned()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let name_c = CString::new(name)?;
+                entry.insert(Plugin::load_from_statics_by_name(&name_c)?)
+            }
+        })
+    }
+}
+
+/// A configured trace processing graph, see `PipelineBuilder`
+pub struct Pipeline {
+    graph: Graph,
+    components: BTreeMap<String, PipelineComponent>,
+    _plugins: BTreeMap<String, Plugin>,
+}
+
+impl Pipeline {
+    pub fn component(&self, name: &str) -> Option<&PipelineComponent> {
+        self.components.get(name)
+    }
+
+    pub fn run_once(&mut self) -> BtResult<RunStatus> {
+        self.graph.run_once()
+    }
+
+    /// Run the graph until all the sink components are finished
+    pub fn run(&mut self) -> BtResult<()> {
+        loop {
+            match self.graph.run_once()? {
+                RunStatus::Ok => (),
+                RunStatus::TryAgain => std::thread::yield_now(),
+                RunStatus::End => return Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_selector_display() {
+        assert_eq!(PortSelector::from(2).to_string(), "#2");
+        assert_eq!(PortSelector::from("out").to_string(), "'out'");
+        assert_eq!(
+            Error::PortNotFound(
+                "muxer".to_owned(),
+                "filter",
+                "output",
+                PortSelector::from(1).to_string()
+            )
+            .to_string(),
+            "The filter component 'muxer' has no output port #1"
+        );
+    }
+}
@@ -1,44 +1,184 @@
+use crate::util::opt_owned_cstr;
 use crate::{
-    ffi, BtResult, BtResultExt, ComponentClassFilter, ComponentClassSink, ComponentClassSource,
-    Error,
+    ffi, BtResult, BtResultExt, ComponentClass, ComponentClassFilter, ComponentClassSink,
+    ComponentClassSource, Error,
 };
-use std::{ffi::CStr, ptr};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
 
 pub struct Plugin {
     inner: *const ffi::bt_plugin,
 }
 
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct PluginVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub extra: Option<String>,
+}
+
+/// Where `Plugin::find` and `PluginSet::find_all` look for plugins
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct PluginLocations {
+    /// The directories of the `BABELTRACE_PLUGIN_PATH` environment variable
+    pub std_env_var: bool,
+    /// `$HOME/.local/lib/babeltrace2/plugins`
+    pub user_dir: bool,
+    /// The system plugin directory
+    pub sys_dir: bool,
+    /// The plugins registered with `register_static_plugins!`
+    pub statics: bool,
+}
+
+impl PluginLocations {
+    pub const STATICS: Self = PluginLocations {
+        std_env_var: false,
+        user_dir: false,
+        sys_dir: false,
+        statics: true,
+    };
+
+    pub const ALL: Self = PluginLocations {
+        std_env_var: true,
+        user_dir: true,
+        sys_dir: true,
+        statics: true,
+    };
+}
+
 impl Plugin {
     pub fn load_from_statics_by_name(name: &CStr) -> BtResult<Self> {
         log::debug!("Loading static plugin '{}'", name.to_string_lossy());
+        Self::find(name, PluginLocations::STATICS, false)
+    }
 
-        let find_in_std_env_var = 0;
-        let find_in_user_dir = 0;
-        let find_in_sys_dir = 0;
-        let find_in_static = 1;
-        let fail_on_load_error = 0;
+    /// Find the plugin named `name`, in the order of the `PluginLocations` fields
+    pub fn find(
+        name: &CStr,
+        locations: PluginLocations,
+        fail_on_load_error: bool,
+    ) -> BtResult<Self> {
+        log::debug!(
+            "Finding plugin '{}' in {:?}",
+            name.to_string_lossy(),
+            locations
+        );
 
         let mut inner = ptr::null();
-        unsafe {
+        let status = unsafe {
             ffi::bt_plugin_find(
                 name.as_ptr(),
-                find_in_std_env_var,
-                find_in_user_dir,
-                find_in_sys_dir,
-                find_in_static,
-                fail_on_load_error,
+                locations.std_env_var as _,
+                locations.user_dir as _,
+                locations.sys_dir as _,
+                locations.statics as _,
+                fail_on_load_error as _,
                 &mut inner,
             )
+        };
+        if status == ffi::bt_plugin_find_status::BT_PLUGIN_FIND_STATUS_NOT_FOUND {
+            let name = name.to_string_lossy().into_owned();
+            return Err(if locations == PluginLocations::STATICS {
+                Error::StaticPluginNotRegistered(name)
+            } else {
+                Error::PluginNotFound(name)
+            });
         }
-        .capi_result()?;
+        status
+            .capi_result()
+            .map_err(Error::with_current_thread_causes)?;
 
         Ok(Plugin { inner })
     }
 
+    /// Take a reference on a plugin borrowed from a plugin set
+    fn from_raw_borrowed(inner: *const ffi::bt_plugin) -> Self {
+        debug_assert!(!inner.is_null());
+        unsafe { ffi::bt_plugin_get_ref(inner) };
+        Plugin { inner }
+    }
+
+    pub fn name(&self) -> BtResult<String> {
+        let name = unsafe { ffi::bt_plugin_get_name(self.inner) };
+        if name.is_null() {
+            return Err(Error::ResourceBorrow);
+        }
+        Ok(unsafe { CStr::from_ptr(name) }.to_str()?.to_owned())
+    }
+
+    pub fn description(&self) -> BtResult<Option<String>> {
+        opt_owned_cstr(unsafe { ffi::bt_plugin_get_description(self.inner) })
+    }
+
+    pub fn author(&self) -> BtResult<Option<String>> {
+        opt_owned_cstr(unsafe { ffi::bt_plugin_get_author(self.inner) })
+    }
+
+    pub fn license(&self) -> BtResult<Option<String>> {
+        opt_owned_cstr(unsafe { ffi::bt_plugin_get_license(self.inner) })
+    }
+
+    /// The path of the plugin's shared object, `None` for static plugins
+    pub fn path(&self) -> BtResult<Option<String>> {
+        opt_owned_cstr(unsafe { ffi::bt_plugin_get_path(self.inner) })
+    }
+
+    pub fn version(&self) -> BtResult<Option<PluginVersion>> {
+        let (mut major, mut minor, mut patch) = (0, 0, 0);
+        let mut extra = ptr::null();
+        let avail = unsafe {
+            ffi::bt_plugin_get_version(self.inner, &mut major, &mut minor, &mut patch, &mut extra)
+        };
+        if avail == ffi::bt_property_availability::BT_PROPERTY_AVAILABILITY_AVAILABLE {
+            Ok(Some(PluginVersion {
+                major,
+                minor,
+                patch,
+                extra: opt_owned_cstr(extra)?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn source_component_classes(&self) -> impl Iterator<Item = ComponentClassSource<'_>> {
+        let count = unsafe { ffi::bt_plugin_get_source_component_class_count(self.inner) };
+        (0..count).filter_map(move |index| {
+            let inner = unsafe {
+                ffi::bt_plugin_borrow_source_component_class_by_index_const(self.inner, index)
+            };
+            (!inner.is_null()).then(|| ComponentClass::from_raw(self, inner))
+        })
+    }
+
+    pub fn filter_component_classes(&self) -> impl Iterator<Item = ComponentClassFilter<'_>> {
+        let count = unsafe { ffi::bt_plugin_get_filter_component_class_count(self.inner) };
+        (0..count).filter_map(move |index| {
+            let inner = unsafe {
+                ffi::bt_plugin_borrow_filter_component_class_by_index_const(self.inner, index)
+            };
+            (!inner.is_null()).then(|| ComponentClass::from_raw(self, inner))
+        })
+    }
+
+    pub fn sink_component_classes(&self) -> impl Iterator<Item = ComponentClassSink<'_>> {
+        let count = unsafe { ffi::bt_plugin_get_sink_component_class_count(self.inner) };
+        (0..count).filter_map(move |index| {
+            let inner = unsafe {
+                ffi::bt_plugin_borrow_sink_component_class_by_index_const(self.inner, index)
+            };
+            (!inner.is_null()).then(|| ComponentClass::from_raw(self, inner))
+        })
+    }
+
     pub fn borrow_source_component_class_by_name(
         &self,
         name: &CStr,
-    ) -> BtResult<ComponentClassSource> {
+    ) -> BtResult<ComponentClassSource<'_>> {
         log::debug!("Borrowing source component '{}'", name.to_string_lossy());
         let inner = unsafe {
             ffi::bt_plugin_borrow_source_component_class_by_name_const(self.inner, name.as_ptr())
@@ -46,11 +186,14 @@ impl Plugin {
         if inner.is_null() {
             Err(Error::ResourceBorrow)
         } else {
-            Ok(ComponentClassSource { inner })
+            Ok(ComponentClass::from_raw(self, inner))
         }
     }
 
-    pub fn borrow_sink_component_class_by_name(&self, name: &CStr) -> BtResult<ComponentClassSink> {
+    pub fn borrow_sink_component_class_by_name(
+        &self,
+        name: &CStr,
+    ) -> BtResult<ComponentClassSink<'_>> {
         log::debug!("Borrowing sink component '{}'", name.to_string_lossy());
         let inner = unsafe {
             ffi::bt_plugin_borrow_sink_component_class_by_name_const(self.inner, name.as_ptr())
@@ -58,14 +201,14 @@ impl Plugin {
         if inner.is_null() {
             Err(Error::ResourceBorrow)
         } else {
-            Ok(ComponentClassSink { inner })
+            Ok(ComponentClass::from_raw(self, inner))
         }
     }
 
     pub fn borrow_filter_component_class_by_name(
         &self,
         name: &CStr,
-    ) -> BtResult<ComponentClassFilter> {
+    ) -> BtResult<ComponentClassFilter<'_>> {
         log::debug!("Borrowing filter component '{}'", name.to_string_lossy());
         let inner = unsafe {
             ffi::bt_plugin_borrow_filter_component_class_by_name_const(self.inner, name.as_ptr())
@@ -73,7 +216,7 @@ impl Plugin {
         if inner.is_null() {
             Err(Error::ResourceBorrow)
         } else {
-            Ok(ComponentClassFilter { inner })
+            Ok(ComponentClass::from_raw(self, inner))
         }
     }
 }
@@ -83,3 +226,167 @@ impl Drop for Plugin {
         unsafe { ffi::bt_plugin_put_ref(self.inner) };
     }
 }
+
+impl fmt::Display for PluginVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(extra) = &self.extra {
+            write!(f, "{}", extra)?;
+        }
+        Ok(())
+    }
+}
+
+/// A set of plugins loaded from shared objects or found in the plugin directories.
+///
+/// A location without any plugin yields an empty set. Here and in `Plugin::find`,
+/// plugins that fail to load are skipped unless `fail_on_load_error` is set.
+pub struct PluginSet {
+    inner: *const ffi::bt_plugin_set,
+}
+
+impl PluginSet {
+    /// Load the plugins of the shared object at `path`
+    pub fn load_from_file<P: AsRef<Path>>(path: P, fail_on_load_error: bool) -> BtResult<Self> {
+        let path = path.as_ref();
+        log::debug!("Loading plugins from file '{}'", path.display());
+        let path_c = CString::new(path.as_os_str().as_bytes())?;
+        let mut inner = ptr::null();
+        let status = unsafe {
+            ffi::bt_plugin_find_all_from_file(path_c.as_ptr(), fail_on_load_error as _, &mut inner)
+        };
+        Self::from_find_status(
+            status,
+            ffi::bt_plugin_find_all_from_file_status::BT_PLUGIN_FIND_ALL_FROM_FILE_STATUS_NOT_FOUND,
+            inner,
+        )
+    }
+
+    /// Load the plugins of the shared objects in the directory at `path`,
+    /// and of its subdirectories when `recurse` is set
+    pub fn load_from_dir<P: AsRef<Path>>(
+        path: P,
+        recurse: bool,
+        fail_on_load_error: bool,
+    ) -> BtResult<Self> {
+        let path = path.as_ref();
+        log::debug!(
+            "Loading plugins from directory '{}', recurse={}",
+            path.display(),
+            recurse
+        );
+        let path_c = CString::new(path.as_os_str().as_bytes())?;
+        let mut inner = ptr::null();
+        let status = unsafe {
+            ffi::bt_plugin_find_all_from_dir(
+                path_c.as_ptr(),
+                recurse as _,
+                fail_on_load_error as _,
+                &mut inner,
+            )
+        };
+        Self::from_find_status(
+            status,
+            ffi::bt_plugin_find_all_from_dir_status::BT_PLUGIN_FIND_ALL_FROM_DIR_STATUS_NOT_FOUND,
+            inner,
+        )
+    }
+
+    /// Load the plugins of the `BABELTRACE_PLUGIN_PATH` environment variable directories
+    pub fn load_from_env_var(fail_on_load_error: bool) -> BtResult<Self> {
+        let locations = PluginLocations {
+            std_env_var: true,
+            ..Default::default()
+        };
+        Self::find_all(locations, fail_on_load_error)
+    }
+
+    /// Find all of the plugins of the given locations, the first plugin
+    /// found for a given name wins
+    pub fn find_all(locations: PluginLocations, fail_on_load_error: bool) -> BtResult<Self> {
+        log::debug!("Finding all plugins in {:?}", locations);
+        let mut inner = ptr::null();
+        let status = unsafe {
+            ffi::bt_plugin_find_all(
+                locations.std_env_var as _,
+                locations.user_dir as _,
+                locations.sys_dir as _,
+                locations.statics as _,
+                fail_on_load_error as _,
+                &mut inner,
+            )
+        };
+        Self::from_find_status(
+            status,
+            ffi::bt_plugin_find_all_status::BT_PLUGIN_FIND_ALL_STATUS_NOT_FOUND,
+            inner,
+        )
+    }
+
+    fn from_find_status(
+        status: std::os::raw::c_int,
+        not_found: std::os::raw::c_int,
+        inner: *const ffi::bt_plugin_set,
+    ) -> BtResult<Self> {
+        if status == not_found {
+            Ok(PluginSet { inner: ptr::null() })
+        } else {
+            status
+                .capi_result()
+                .map_err(Error::with_current_thread_causes)?;
+            Ok(PluginSet { inner })
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        if self.inner.is_null() {
+            0
+        } else {
+            unsafe { ffi::bt_plugin_set_get_plugin_count(self.inner) as usize }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn plugin(&self, index: usize) -> Option<Plugin> {
+        if index >= self.len() {
+            None
+        } else {
+            let plugin =
+                unsafe { ffi::bt_plugin_set_borrow_plugin_by_index_const(self.inner, index as _) };
+            Some(Plugin::from_raw_borrowed(plugin))
+        }
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = Plugin> + '_ {
+        (0..self.len()).filter_map(move |index| self.plugin(index))
+    }
+}
+
+impl Drop for PluginSet {
+    fn drop(&mut self) {
+        if !self.inner.is_null() {
+            unsafe { ffi::bt_plugin_set_put_ref(self.inner) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_display() {
+        let mut version = PluginVersion {
+            major: 2,
+            minor: 0,
+            patch: 4,
+            extra: None,
+        };
+        assert_eq!(version.to_string(), "2.0.4");
+        version.extra = Some("-rc1".to_owned());
+        assert_eq!(version.to_string(), "2.0.4-rc1");
+    }
+}
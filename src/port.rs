@@ -1,4 +1,4 @@
-use crate::ffi;
+use crate::{ffi, util, BtResult};
 
 pub type InputPort = Port<ffi::bt_port_input>;
 pub type OutputPort = Port<ffi::bt_port_output>;
@@ -8,9 +8,26 @@ pub struct Port<T> {
     pub(crate) inner: *const T,
 }
 
+impl<T> Port<T> {
+    pub fn name(&self) -> BtResult<Option<String>> {
+        util::opt_owned_cstr(unsafe { ffi::bt_port_get_name(self.inner as *const ffi::bt_port) })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        unsafe { ffi::bt_port_is_connected(self.inner as *const ffi::bt_port) != 0 }
+    }
+}
+
 pub type SelfComponentInputPort = SelfComponentPort<ffi::bt_self_component_port_input>;
 pub type SelfComponentOutputPort = SelfComponentPort<ffi::bt_self_component_port_output>;
 
 pub struct SelfComponentPort<T> {
     pub(crate) inner: *mut T,
 }
+
+impl<T> SelfComponentPort<T> {
+    pub fn is_connected(&self) -> bool {
+        // bt_self_component_port_*_as_port_*_const and bt_port_*_as_port_const are casts
+        unsafe { ffi::bt_port_is_connected(self.inner as *const ffi::bt_port) != 0 }
+    }
+}
@@ -1,8 +1,11 @@
+use crate::error_cause::append_current_thread_error_cause;
 use crate::{
-    ffi, BtResult, ComponentClassSink, ComponentSink, Error, Message, MessageIterator, MessageType,
-    NextStatus, OwnedEvent, Plugin, SelfComponentSink, StreamProperties, TraceProperties,
+    ffi, BtResult, ClockNanoseconds, ComponentClassSink, ComponentSink, Discarded, Error, Message,
+    MessageIterator, MessageType, NextStatus, OwnedEvent, OwnedMessage, Plugin, SelfComponentSink,
+    StreamClass, StreamClassId, StreamClassProperties, StreamProperties, Trace, TraceIndex,
+    TraceProperties,
 };
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::convert::{AsMut, AsRef};
 use std::ffi::{c_void, CStr};
 
@@ -20,7 +23,7 @@ impl ProxyPlugin {
         Ok(ProxyPlugin(Plugin::load_from_statics_by_name(name)?))
     }
 
-    pub fn borrow_output_sink_component_class_by_name(&self) -> BtResult<ComponentClassSink> {
+    pub fn borrow_output_sink_component_class_by_name(&self) -> BtResult<ComponentClassSink<'_>> {
         let name = Self::output_name();
         self.0.borrow_sink_component_class_by_name(name)
     }
@@ -41,9 +44,33 @@ impl ProxyPlugin {
 #[derive(Default)]
 pub struct ProxyPluginState {
     pub(crate) msg_iter: Option<MessageIterator>,
-    pub(crate) trace_properties: TraceProperties,
+    /// Properties of each trace, indexed by `TraceIndex`
+    pub(crate) trace_properties: Vec<TraceProperties>,
+    /// Traces encountered so far, a reference is held on each, indexed by `TraceIndex`
+    traces: Vec<Trace>,
     pub(crate) stream_properties: BTreeSet<StreamProperties>,
-    pub(crate) events: VecDeque<OwnedEvent>,
+    pub(crate) stream_classes: BTreeMap<(TraceIndex, StreamClassId), StreamClassProperties>,
+    /// Events and discarded notices, in the order they were consumed
+    pub(crate) items: VecDeque<QueuedItem>,
+    /// When set, event messages are retained in place of converting them into `OwnedEvent`s
+    pub(crate) retain_event_messages: bool,
+    /// When set, every message is converted into an `OwnedMessage` and queued in `messages`
+    pub(crate) collect_messages: bool,
+    pub(crate) messages: VecDeque<OwnedMessage>,
+    /// When set, the upstream message iterator is kept once it ends and the sink
+    /// doesn't report the end to the graph, so that the iterator can still seek
+    pub(crate) seekable: bool,
+    /// Set once the upstream message iterator ended, cleared by seeking
+    pub(crate) upstream_ended: bool,
+}
+
+/// An item consumed by the proxy sink
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum QueuedItem {
+    Event(OwnedEvent),
+    /// An event message, retained in place of an `OwnedEvent`, and its trace index
+    EventMessage(Message, TraceIndex),
+    Discarded(Discarded),
 }
 
 /// Plugin state, dynamically allocated, shared with the caller and
@@ -149,6 +176,11 @@ impl ProxyPluginState {
     fn consume(&mut self) -> Result<ConsumeSuccessCode, ConsumeError> {
         use ffi::bt_component_class_sink_consume_method_status::*;
 
+        if self.upstream_ended {
+            // Nothing to consume until the iterator seeks
+            return Ok(BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_AGAIN);
+        }
+
         // Consume a batch of messages from the upstream message iterator
         let msg_iter = self.msg_iter.as_mut().ok_or(ConsumeError::NullIterator)?;
         let (next_status, msg_array) = msg_iter
@@ -162,42 +194,53 @@ impl ProxyPluginState {
                 for msg_ref in messages.iter() {
                     let msg = Message::from_raw(*msg_ref);
                     let msg_type = msg.get_type();
+                    if self.collect_messages {
+                        if msg_type == MessageType::StreamBeginning {
+                            self.update_stream_metadata(&msg)?;
+                        }
+                        let mut owned = msg.to_owned_message()?;
+                        owned.set_trace_index(self.trace_index_of(&msg)?);
+                        self.messages.push_back(owned);
+                        continue;
+                    }
                     match msg_type {
-                        // Populate trace and stream properties at the beginning, this is idempotent
-                        // when updating a trace upon encountering multiple stream beginning messages
-                        // as they all refer to a single trace
-                        MessageType::StreamBeginning => {
-                            let stream = msg
-                                .stream_beginning_borrow_stream()
-                                .map_err(ConsumeError::StreamBorrow)?;
-                            let props = stream.properties()?;
-                            self.stream_properties.insert(props);
-
-                            let trace = stream.trace()?;
-                            self.trace_properties = trace.properties()?;
+                        MessageType::StreamBeginning => self.update_stream_metadata(&msg)?,
+                        MessageType::Event if self.retain_event_messages => {
+                            let trace_index = self.trace_index_of(&msg)?;
+                            self.items
+                                .push_back(QueuedItem::EventMessage(msg, trace_index));
                         }
                         MessageType::Event => {
-                            let event = msg
+                            let mut event = msg
                                 .borrow_event()
                                 .map_err(ConsumeError::EventBorrow)?
                                 .to_owned()?;
-                            self.events.push_back(event);
+                            event.trace_index = self.trace_index_of(&msg)?;
+                            self.items.push_back(QueuedItem::Event(event));
+                        }
+                        MessageType::DiscardedEvents | MessageType::DiscardedPackets => {
+                            let mut discarded = msg.to_discarded()?;
+                            discarded.trace_index = self.trace_index_of(&msg)?;
+                            log::debug!(
+                                "Discarded {:?} in stream ID={} of trace UUID={:?}, count={:?}",
+                                discarded.kind,
+                                discarded.stream_id,
+                                self.trace_properties[discarded.trace_index].uuid,
+                                discarded.count
+                            );
+                            self.items.push_back(QueuedItem::Discarded(discarded));
                         }
-                        // TODO - make this a type we surface
-                        MessageType::DiscardedEvents => log::debug!(
-                            "Tracer discarded events in trace UUID={:?}",
-                            self.trace_properties.uuid
-                        ),
-                        MessageType::DiscardedPackets => log::debug!(
-                            "Tracer discarded packets in trace UUID={:?}",
-                            self.trace_properties.uuid
-                        ),
                         _ => (),
                     }
                 }
 
                 BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_OK
             }
+            NextStatus::End if self.seekable => {
+                // The sink only ends with the graph, so that the iterator can seek back
+                self.upstream_ended = true;
+                BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_AGAIN
+            }
             NextStatus::End => {
                 let _ = self.msg_iter.take(); // Done with iterator, drop it now
                 BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_END
@@ -206,6 +249,96 @@ impl ProxyPluginState {
         };
         Ok(retcode)
     }
+
+    // Seeking happens between graph runs, outside of the sink's consume method.
+    // libbabeltrace2 only requires the iterator's graph to be configured, which the
+    // first run does, and the iterator not to be finalized. An ended iterator can seek.
+    pub(crate) fn seek_beginning(&mut self) -> BtResult<()> {
+        let msg_iter = self.configured_iterator()?;
+        if !msg_iter.can_seek_beginning()? {
+            return Err(Error::SeekUnsupported("to the beginning".to_owned()));
+        }
+        msg_iter.seek_beginning()?;
+        self.reset_after_seek();
+        Ok(())
+    }
+
+    pub(crate) fn seek_ns_from_origin(&mut self, ns_from_origin: ClockNanoseconds) -> BtResult<()> {
+        let msg_iter = self.configured_iterator()?;
+        if !msg_iter.can_seek_ns_from_origin(ns_from_origin)? {
+            return Err(Error::SeekUnsupported(format!(
+                "to {} ns from origin",
+                ns_from_origin
+            )));
+        }
+        msg_iter.seek_ns_from_origin(ns_from_origin)?;
+        self.reset_after_seek();
+        Ok(())
+    }
+
+    fn configured_iterator(&mut self) -> BtResult<&mut MessageIterator> {
+        self.msg_iter
+            .as_mut()
+            .ok_or_else(|| Error::SeekUnsupported("before the graph is configured".to_owned()))
+    }
+
+    /// Drop everything consumed before a seek
+    fn reset_after_seek(&mut self) {
+        self.items.clear();
+        self.messages.clear();
+        self.upstream_ended = false;
+    }
+
+    fn trace_index_of(&mut self, msg: &Message) -> BtResult<TraceIndex> {
+        match msg.borrow_trace()? {
+            Some(trace) => self.trace_index(&trace),
+            None => Ok(0),
+        }
+    }
+
+    /// Traces are indexed in the order they're first encountered
+    fn trace_index(&mut self, trace: &Trace) -> BtResult<TraceIndex> {
+        if let Some(idx) = self.traces.iter().position(|t| t.inner == trace.inner) {
+            return Ok(idx);
+        }
+        let props = trace.properties()?;
+        unsafe { ffi::bt_trace_get_ref(trace.inner) };
+        self.traces.push(Trace { inner: trace.inner });
+        self.trace_properties.push(props);
+        Ok(self.traces.len() - 1)
+    }
+
+    // Populate trace and stream properties at the beginning, this is idempotent
+    // when updating a trace upon encountering multiple stream beginning messages
+    // that refer to the same trace
+    fn update_stream_metadata(&mut self, msg: &Message) -> Result<(), ConsumeError> {
+        let stream = msg
+            .stream_beginning_borrow_stream()
+            .map_err(ConsumeError::StreamBorrow)?;
+        let trace = stream.trace()?;
+        let trace_index = self.trace_index(&trace)?;
+        self.trace_properties[trace_index] = trace.properties()?;
+
+        let mut props = stream.properties()?;
+        props.trace_index = trace_index;
+        self.stream_properties.insert(props);
+
+        // Catalog every stream class of the trace class, not just
+        // the ones with streams yet
+        for class in StreamClass::all_from_trace(&trace) {
+            let props = class.properties()?;
+            self.stream_classes.insert((trace_index, props.id), props);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ProxyPluginState {
+    fn drop(&mut self) {
+        for trace in self.traces.drain(..) {
+            unsafe { ffi::bt_trace_put_ref(trace.inner) };
+        }
+    }
 }
 
 #[no_mangle]
@@ -301,13 +434,14 @@ extern "C" fn proxy_sink_consume(
         Ok(retcode) => retcode,
         Err(e) => {
             log::error!("Proxy sink cannot consume. {}", e);
+            append_current_thread_error_cause(file!(), line!(), &e.to_string());
             BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_ERROR
         }
     }
 }
 
 /// Plugin descriptor related data, pointers to this data
-/// will end up in special linker sections
+/// are placed in special linker sections by `register_static_plugins!`
 /// so libbabeltrace2 can discover it
 pub mod proxy_plugin_descriptors {
     use super::*;
@@ -1,9 +1,14 @@
-use crate::{ffi, BtResult, BtResultExt, Error, MessageIterator, SelfComponentInputPort};
+use crate::{
+    ffi, BtResult, BtResultExt, Error, MessageIterator, SelfComponentInputPort,
+    SelfComponentOutputPort,
+};
 use std::ffi::CStr;
 use std::os::raw::c_void;
 use std::ptr;
 
 pub type SelfComponentSink = SelfComponent<ffi::bt_self_component_sink>;
+pub type SelfComponentFilter = SelfComponent<ffi::bt_self_component_filter>;
+pub type SelfComponentSource = SelfComponent<ffi::bt_self_component_source>;
 
 pub struct SelfComponent<T> {
     pub(crate) inner: *mut T,
@@ -40,6 +45,10 @@ impl SelfComponentSink {
         .capi_result()
     }
 
+    pub fn get_input_port_count(&self) -> u64 {
+        unsafe { ffi::bt_component_sink_get_input_port_count(self.inner as *const _) }
+    }
+
     pub fn borrow_input_port_by_index(&mut self, index: u64) -> BtResult<SelfComponentInputPort> {
         let port =
             unsafe { ffi::bt_self_component_sink_borrow_input_port_by_index(self.inner, index) };
@@ -62,3 +71,101 @@ impl SelfComponentSink {
         Ok(MessageIterator { inner: iter })
     }
 }
+
+impl SelfComponentFilter {
+    pub fn from_raw(inner: *mut ffi::bt_self_component_filter) -> Self {
+        debug_assert!(!inner.is_null());
+        SelfComponentFilter { inner }
+    }
+
+    /// You can only call this function from within
+    /// the initialization, "input port connected", and "output port connected" methods.
+    pub fn add_input_port(&mut self, name: &CStr) -> BtResult<()> {
+        unsafe {
+            ffi::bt_self_component_filter_add_input_port(
+                self.inner,
+                name.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .capi_result()
+    }
+
+    /// You can only call this function from within
+    /// the initialization, "input port connected", and "output port connected" methods.
+    pub fn add_output_port(&mut self, name: &CStr) -> BtResult<()> {
+        unsafe {
+            ffi::bt_self_component_filter_add_output_port(
+                self.inner,
+                name.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .capi_result()
+    }
+
+    pub fn get_input_port_count(&self) -> u64 {
+        unsafe { ffi::bt_component_filter_get_input_port_count(self.inner as *const _) }
+    }
+
+    pub fn get_output_port_count(&self) -> u64 {
+        unsafe { ffi::bt_component_filter_get_output_port_count(self.inner as *const _) }
+    }
+
+    pub fn borrow_input_port_by_index(&mut self, index: u64) -> BtResult<SelfComponentInputPort> {
+        let port =
+            unsafe { ffi::bt_self_component_filter_borrow_input_port_by_index(self.inner, index) };
+        if port.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(SelfComponentInputPort { inner: port })
+        }
+    }
+
+    pub fn borrow_output_port_by_index(&mut self, index: u64) -> BtResult<SelfComponentOutputPort> {
+        let port =
+            unsafe { ffi::bt_self_component_filter_borrow_output_port_by_index(self.inner, index) };
+        if port.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(SelfComponentOutputPort { inner: port })
+        }
+    }
+}
+
+impl SelfComponentSource {
+    pub fn from_raw(inner: *mut ffi::bt_self_component_source) -> Self {
+        debug_assert!(!inner.is_null());
+        SelfComponentSource { inner }
+    }
+
+    /// You can only call this function from within
+    /// the initialization and "output port connected" methods.
+    pub fn add_output_port(&mut self, name: &CStr) -> BtResult<()> {
+        unsafe {
+            ffi::bt_self_component_source_add_output_port(
+                self.inner,
+                name.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        }
+        .capi_result()
+    }
+
+    pub fn get_output_port_count(&self) -> u64 {
+        unsafe { ffi::bt_component_source_get_output_port_count(self.inner as *const _) }
+    }
+
+    pub fn borrow_output_port_by_index(&mut self, index: u64) -> BtResult<SelfComponentOutputPort> {
+        let port =
+            unsafe { ffi::bt_self_component_source_borrow_output_port_by_index(self.inner, index) };
+        if port.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(SelfComponentOutputPort { inner: port })
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{
+    ffi, BtResult, BtResultExt, Error, EventClass, EventFields, Message, MessageIterator, Packet,
+    SelfComponent, SelfComponentInputPort, SelfComponentOutputPort, Stream, StreamClass,
+};
+use std::os::raw::c_void;
+use std::ptr;
+
+/// A message iterator of a component's output port, as seen by
+/// the component implementing it.
+///
+/// Messages are created through the message iterator, clock snapshot
+/// values are in clock cycles.
+pub struct SelfMessageIterator {
+    pub(crate) inner: *mut ffi::bt_self_message_iterator,
+}
+
+impl SelfMessageIterator {
+    pub fn from_raw(inner: *mut ffi::bt_self_message_iterator) -> Self {
+        debug_assert!(!inner.is_null());
+        SelfMessageIterator { inner }
+    }
+
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn set_c_user_data_ptr(&mut self, user_data: *mut c_void) {
+        unsafe { ffi::bt_self_message_iterator_set_data(self.inner, user_data) };
+    }
+
+    pub fn get_c_user_data_ptr(&mut self) -> *mut c_void {
+        unsafe { ffi::bt_self_message_iterator_get_data(self.inner) }
+    }
+
+    /// The component of this message iterator, `T` must match the component's type
+    pub(crate) fn borrow_component<T>(&mut self) -> SelfComponent<T> {
+        let component = unsafe { ffi::bt_self_message_iterator_borrow_component(self.inner) };
+        debug_assert!(!component.is_null());
+        SelfComponent {
+            inner: component as *mut T,
+        }
+    }
+
+    pub fn borrow_port(&mut self) -> SelfComponentOutputPort {
+        let port = unsafe { ffi::bt_self_message_iterator_borrow_port(self.inner) };
+        SelfComponentOutputPort { inner: port }
+    }
+
+    /// Create a message iterator on one of the component's input ports
+    pub fn create_message_iterator(
+        &mut self,
+        port: &SelfComponentInputPort,
+    ) -> BtResult<MessageIterator> {
+        let mut iter = ptr::null_mut();
+        unsafe {
+            ffi::bt_message_iterator_create_from_message_iterator(self.inner, port.inner, &mut iter)
+        }
+        .capi_result()?;
+        Ok(MessageIterator { inner: iter })
+    }
+
+    pub fn create_stream_beginning_message(&mut self, stream: &Stream) -> BtResult<Message> {
+        created(unsafe { ffi::bt_message_stream_beginning_create(self.inner, stream.inner) })
+    }
+
+    pub fn create_stream_end_message(&mut self, stream: &Stream) -> BtResult<Message> {
+        created(unsafe { ffi::bt_message_stream_end_create(self.inner, stream.inner) })
+    }
+
+    pub fn create_packet_beginning_message(
+        &mut self,
+        packet: &Packet,
+        clock_snapshot: Option<u64>,
+    ) -> BtResult<Message> {
+        let class = packet.class()?;
+        check_packets_supported(&class)?;
+        check_clock_snapshot(
+            class.packets_have_beginning_clock_snapshot(),
+            clock_snapshot,
+        )?;
+        created(unsafe {
+            match clock_snapshot {
+                Some(cs) => ffi::bt_message_packet_beginning_create_with_default_clock_snapshot(
+                    self.inner,
+                    packet.inner,
+                    cs,
+                ),
+                None => ffi::bt_message_packet_beginning_create(self.inner, packet.inner),
+            }
+        })
+    }
+
+    pub fn create_packet_end_message(
+        &mut self,
+        packet: &Packet,
+        clock_snapshot: Option<u64>,
+    ) -> BtResult<Message> {
+        let class = packet.class()?;
+        check_packets_supported(&class)?;
+        check_clock_snapshot(class.packets_have_end_clock_snapshot(), clock_snapshot)?;
+        created(unsafe {
+            match clock_snapshot {
+                Some(cs) => ffi::bt_message_packet_end_create_with_default_clock_snapshot(
+                    self.inner,
+                    packet.inner,
+                    cs,
+                ),
+                None => ffi::bt_message_packet_end_create(self.inner, packet.inner),
+            }
+        })
+    }
+
+    /// Create an event message, in `packet` when the stream supports packets,
+    /// with the event's fields set to `fields`.
+    ///
+    /// `clock_snapshot` is required when the stream class has a default clock.
+    pub fn create_event_message(
+        &mut self,
+        event_class: &EventClass,
+        stream: &Stream,
+        packet: Option<&Packet>,
+        clock_snapshot: Option<u64>,
+        fields: &EventFields<'_>,
+    ) -> BtResult<Message> {
+        let class = stream.class()?;
+        match packet {
+            Some(_) => check_packets_supported(&class)?,
+            None if class.supports_packets() => return Err(Error::PacketRequired),
+            None => (),
+        }
+        check_clock_snapshot(class.has_default_clock(), clock_snapshot)?;
+        let ec = event_class.inner;
+        let mut msg = created(unsafe {
+            match (packet, clock_snapshot) {
+                (Some(p), Some(cs)) => {
+                    ffi::bt_message_event_create_with_packet_and_default_clock_snapshot(
+                        self.inner, ec, p.inner, cs,
+                    )
+                }
+                (Some(p), None) => {
+                    ffi::bt_message_event_create_with_packet(self.inner, ec, p.inner)
+                }
+                (None, Some(cs)) => ffi::bt_message_event_create_with_default_clock_snapshot(
+                    self.inner,
+                    ec,
+                    stream.inner,
+                    cs,
+                ),
+                (None, None) => ffi::bt_message_event_create(self.inner, ec, stream.inner),
+            }
+        })?;
+        msg.set_event_fields(fields)?;
+        Ok(msg)
+    }
+}
+
+fn check_packets_supported(class: &StreamClass) -> BtResult<()> {
+    if class.supports_packets() {
+        Ok(())
+    } else {
+        Err(Error::PacketsNotSupported)
+    }
+}
+
+/// Checks that a clock snapshot is given exactly when the stream class expects one
+fn check_clock_snapshot(expected: bool, clock_snapshot: Option<u64>) -> BtResult<()> {
+    match (expected, clock_snapshot) {
+        (true, None) => Err(Error::ClockSnapshotRequired),
+        (false, Some(_)) => Err(Error::UnexpectedClockSnapshot),
+        _ => Ok(()),
+    }
+}
+
+fn created(msg: *mut ffi::bt_message) -> BtResult<Message> {
+    if msg.is_null() {
+        Err(Error::Memory)
+    } else {
+        Ok(Message::from_raw(msg))
+    }
+}
@@ -0,0 +1,277 @@
+use crate::message_iterator::UpstreamMessageIterators;
+use crate::util::catch_panic;
+use crate::{ffi, BtResult, Message, NextStatus, RunStatus, SelfComponentSink, ValueRef};
+use std::ffi::c_void;
+use std::marker::PhantomData;
+
+/// A custom sink component class.
+///
+/// Use `sink_plugin_descriptors!` to generate the plugin and component class
+/// descriptors, the sink's methods are dispatched to the trait implementation.
+pub trait Sink: Sized + 'static {
+    /// Create the sink, add its input ports here
+    fn initialize(
+        component: &mut SelfComponentSink,
+        params: Option<ValueRef<'_>>,
+    ) -> BtResult<Self>;
+
+    /// Called once the graph is configured, after a message iterator
+    /// was created for each input port
+    fn graph_is_configured(&mut self, _component: &mut SelfComponentSink) -> BtResult<()> {
+        Ok(())
+    }
+
+    /// Consume a batch of messages from the upstream message iterator of the
+    /// input port at index `input_port`, called for each port with messages.
+    ///
+    /// Returning `RunStatus::End` ends the sink, it otherwise ends
+    /// once all of the upstream message iterators have ended.
+    fn consume(&mut self, input_port: u64, messages: &[Message]) -> BtResult<RunStatus>;
+
+    fn finalize(&mut self) {}
+}
+
+/// Component user data, owned by the component until it's finalized
+struct SinkState<S: Sink> {
+    sink: S,
+    upstream: UpstreamMessageIterators,
+}
+
+impl<S: Sink> SinkState<S> {
+    fn from_component<'a>(component: &mut SelfComponentSink) -> Option<&'a mut Self> {
+        let state = component.get_c_user_data_ptr() as *mut Self;
+        if state.is_null() {
+            None
+        } else {
+            Some(unsafe { &mut (*state) })
+        }
+    }
+
+    fn create_message_iterators(&mut self, component: &mut SelfComponentSink) -> BtResult<()> {
+        for index in 0..component.get_input_port_count() {
+            let port = component.borrow_input_port_by_index(index)?;
+            if port.is_connected() {
+                self.upstream
+                    .push(index, component.create_message_iterator(&port)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn consume(&mut self) -> BtResult<RunStatus> {
+        let (status, batches) = self.upstream.next_messages()?;
+        if batches.is_empty() {
+            return Ok(match status {
+                NextStatus::End => RunStatus::End,
+                NextStatus::TryAgain => RunStatus::TryAgain,
+                NextStatus::Ok => RunStatus::Ok,
+            });
+        }
+        let mut run_status = RunStatus::Ok;
+        for (input_port, messages) in batches {
+            match self.sink.consume(input_port, &messages)? {
+                RunStatus::End => return Ok(RunStatus::End),
+                RunStatus::TryAgain => run_status = RunStatus::TryAgain,
+                RunStatus::Ok => (),
+            }
+        }
+        Ok(run_status)
+    }
+}
+
+/// Method glue between libbabeltrace2 and a `Sink` implementation,
+/// used by `sink_plugin_descriptors!`
+pub struct SinkMethods<S: Sink>(PhantomData<S>);
+
+impl<S: Sink> SinkMethods<S> {
+    pub extern "C" fn initialize(
+        sink: *mut ffi::bt_self_component_sink,
+        _config: *mut ffi::bt_self_component_sink_configuration,
+        params: *const ffi::bt_value,
+        _initialize_method_data: *mut c_void,
+    ) -> ffi::bt_component_class_initialize_method_status::Type {
+        use ffi::bt_component_class_initialize_method_status::*;
+
+        let mut component = SelfComponentSink::from_raw(sink);
+        match catch_panic(|| S::initialize(&mut component, ValueRef::from_raw(params))) {
+            Ok(sink) => {
+                let state = Box::new(SinkState {
+                    sink,
+                    upstream: UpstreamMessageIterators::default(),
+                });
+                component.set_c_user_data_ptr(Box::into_raw(state) as *mut c_void);
+                BT_COMPONENT_CLASS_INITIALIZE_METHOD_STATUS_OK
+            }
+            Err(e) => {
+                log::error!("Failed to initialize sink. {}", e);
+                e.append_to_current_thread(file!(), line!());
+                BT_COMPONENT_CLASS_INITIALIZE_METHOD_STATUS_ERROR
+            }
+        }
+    }
+
+    pub extern "C" fn finalize(sink: *mut ffi::bt_self_component_sink) {
+        let mut component = SelfComponentSink::from_raw(sink);
+        let state = component.get_c_user_data_ptr() as *mut SinkState<S>;
+        if !state.is_null() {
+            let mut state = unsafe { Box::from_raw(state) };
+            if let Err(e) = catch_panic(|| {
+                state.sink.finalize();
+                Ok(())
+            }) {
+                log::error!("Failed to finalize sink. {}", e);
+            }
+            component.set_c_user_data_ptr(std::ptr::null_mut());
+        }
+    }
+
+    pub extern "C" fn graph_is_configured(
+        sink: *mut ffi::bt_self_component_sink,
+    ) -> ffi::bt_component_class_sink_graph_is_configured_method_status::Type {
+        use ffi::bt_component_class_sink_graph_is_configured_method_status::*;
+
+        let mut component = SelfComponentSink::from_raw(sink);
+        let state = match SinkState::<S>::from_component(&mut component) {
+            Some(s) => s,
+            None => {
+                log::error!("Sink state is NULL");
+                return BT_COMPONENT_CLASS_SINK_GRAPH_IS_CONFIGURED_METHOD_STATUS_ERROR;
+            }
+        };
+
+        let res = catch_panic(|| {
+            state.create_message_iterators(&mut component)?;
+            state.sink.graph_is_configured(&mut component)
+        });
+        match res {
+            Ok(()) => BT_COMPONENT_CLASS_SINK_GRAPH_IS_CONFIGURED_METHOD_STATUS_OK,
+            Err(e) => {
+                log::error!("Failed to configure sink. {}", e);
+                e.append_to_current_thread(file!(), line!());
+                BT_COMPONENT_CLASS_SINK_GRAPH_IS_CONFIGURED_METHOD_STATUS_ERROR
+            }
+        }
+    }
+
+    pub extern "C" fn consume(
+        sink: *mut ffi::bt_self_component_sink,
+    ) -> ffi::bt_component_class_sink_consume_method_status::Type {
+        use ffi::bt_component_class_sink_consume_method_status::*;
+
+        let mut component = SelfComponentSink::from_raw(sink);
+        let state = match SinkState::<S>::from_component(&mut component) {
+            Some(s) => s,
+            None => {
+                log::error!("Sink cannot consume, state is NULL");
+                return BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_ERROR;
+            }
+        };
+
+        match catch_panic(|| state.consume()) {
+            Ok(RunStatus::Ok) => BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_OK,
+            Ok(RunStatus::TryAgain) => BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_AGAIN,
+            Ok(RunStatus::End) => BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_END,
+            Err(e) => {
+                log::error!("Sink cannot consume. {}", e);
+                e.append_to_current_thread(file!(), line!());
+                BT_COMPONENT_CLASS_SINK_CONSUME_METHOD_STATUS_ERROR
+            }
+        }
+    }
+}
+
+/// Generate the plugin and sink component class descriptors for a `Sink`
+/// implementation, in a module named `$module`.
+///
+/// The plugin is registered by passing the module to `register_static_plugins!`.
+#[macro_export]
+macro_rules! sink_plugin_descriptors {
+    ($module:ident, $plugin_name:literal, $sink_name:literal, $sink:ty) => {
+        pub mod $module {
+            use super::*;
+            use $crate::ffi::*;
+
+            pub const PLUGIN_NAME: &[u8] = concat!($plugin_name, "\0").as_bytes();
+            pub const SINK_COMP_NAME: &[u8] = concat!($sink_name, "\0").as_bytes();
+
+            pub static PLUGIN_DESC: __bt_plugin_descriptor = __bt_plugin_descriptor {
+                name: PLUGIN_NAME.as_ptr() as *const _,
+            };
+
+            pub static SINK_COMP_DESC: __bt_plugin_component_class_descriptor =
+                __bt_plugin_component_class_descriptor {
+                    plugin_descriptor: &PLUGIN_DESC,
+                    name: SINK_COMP_NAME.as_ptr() as *const _,
+                    type_: bt_component_class_type::BT_COMPONENT_CLASS_TYPE_SINK,
+                    methods: __bt_plugin_component_class_descriptor__bindgen_ty_1 {
+                        sink: __bt_plugin_component_class_descriptor__bindgen_ty_1__bindgen_ty_3 {
+                            consume: Some($crate::SinkMethods::<$sink>::consume),
+                        },
+                    },
+                };
+
+            pub static SINK_COMP_CLASS_INIT_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &SINK_COMP_DESC,
+                    type_name: b"sink_initialize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_INITIALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        sink_initialize_method: Some($crate::SinkMethods::<$sink>::initialize),
+                    },
+                };
+
+            pub static SINK_COMP_CLASS_FINI_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &SINK_COMP_DESC,
+                    type_name: b"sink_finalize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_FINALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        sink_finalize_method: Some($crate::SinkMethods::<$sink>::finalize),
+                    },
+                };
+
+            pub static SINK_COMP_CLASS_GRAPH_CONF_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &SINK_COMP_DESC,
+                    type_name: b"sink_graph_is_configured_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_GRAPH_IS_CONFIGURED_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        sink_graph_is_configured_method: Some($crate::SinkMethods::<$sink>::graph_is_configured),
+                    },
+                };
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    struct NullSink;
+
+    impl Sink for NullSink {
+        fn initialize(_: &mut SelfComponentSink, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+            Ok(NullSink)
+        }
+
+        fn consume(&mut self, _: u64, _: &[Message]) -> BtResult<RunStatus> {
+            Ok(RunStatus::Ok)
+        }
+    }
+
+    #[allow(dead_code)]
+    mod descriptors {
+        use super::*;
+        crate::sink_plugin_descriptors!(null_sink_descriptors, "null", "sink", NullSink);
+    }
+    use descriptors::null_sink_descriptors;
+
+    #[test]
+    fn cstrings_are_valid() {
+        let plugin = CStr::from_bytes_with_nul(null_sink_descriptors::PLUGIN_NAME).unwrap();
+        let sink = CStr::from_bytes_with_nul(null_sink_descriptors::SINK_COMP_NAME).unwrap();
+        assert_eq!(plugin.to_str().unwrap(), "null");
+        assert_eq!(sink.to_str().unwrap(), "sink");
+    }
+}
@@ -0,0 +1,259 @@
+use crate::util::catch_panic;
+use crate::{
+    ffi, BtResult, Message, NextStatus, SelfComponentSource, SelfMessageIterator, ValueRef,
+};
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+
+/// A custom source component class.
+///
+/// The trace, stream and event classes are typically created in `initialize`,
+/// see `TraceClass`. Use `source_plugin_descriptors!` to generate the plugin
+/// and component class descriptors.
+pub trait Source: Sized + 'static {
+    /// Create the source, add its output ports here
+    fn initialize(
+        component: &mut SelfComponentSource,
+        params: Option<ValueRef<'_>>,
+    ) -> BtResult<Self>;
+
+    /// Emit the next messages of `msg_iter`'s output port by pushing them to `output`.
+    ///
+    /// Return `NextStatus::End` once the port has no more messages, the messages
+    /// pushed along with it are still emitted.
+    fn next(
+        &mut self,
+        msg_iter: &mut SelfMessageIterator,
+        output: &mut VecDeque<Message>,
+    ) -> BtResult<NextStatus>;
+
+    fn finalize(&mut self) {}
+}
+
+/// Message iterator user data, owned by the message iterator until it's finalized
+#[derive(Default)]
+struct SourceIterState {
+    output: VecDeque<Message>,
+    ended: bool,
+}
+
+/// Method glue between libbabeltrace2 and a `Source` implementation,
+/// used by `source_plugin_descriptors!`
+pub struct SourceMethods<S: Source>(PhantomData<S>);
+
+impl<S: Source> SourceMethods<S> {
+    pub extern "C" fn initialize(
+        source: *mut ffi::bt_self_component_source,
+        _config: *mut ffi::bt_self_component_source_configuration,
+        params: *const ffi::bt_value,
+        _initialize_method_data: *mut c_void,
+    ) -> ffi::bt_component_class_initialize_method_status::Type {
+        use ffi::bt_component_class_initialize_method_status::*;
+
+        let mut component = SelfComponentSource::from_raw(source);
+        match catch_panic(|| S::initialize(&mut component, ValueRef::from_raw(params))) {
+            Ok(source) => {
+                component.set_c_user_data_ptr(Box::into_raw(Box::new(source)) as *mut c_void);
+                BT_COMPONENT_CLASS_INITIALIZE_METHOD_STATUS_OK
+            }
+            Err(e) => {
+                log::error!("Failed to initialize source. {}", e);
+                e.append_to_current_thread(file!(), line!());
+                BT_COMPONENT_CLASS_INITIALIZE_METHOD_STATUS_ERROR
+            }
+        }
+    }
+
+    pub extern "C" fn finalize(source: *mut ffi::bt_self_component_source) {
+        let mut component = SelfComponentSource::from_raw(source);
+        let source = component.get_c_user_data_ptr() as *mut S;
+        if !source.is_null() {
+            let mut source = unsafe { Box::from_raw(source) };
+            if let Err(e) = catch_panic(|| {
+                source.finalize();
+                Ok(())
+            }) {
+                log::error!("Failed to finalize source. {}", e);
+            }
+            component.set_c_user_data_ptr(std::ptr::null_mut());
+        }
+    }
+
+    pub extern "C" fn msg_iter_initialize(
+        msg_iter: *mut ffi::bt_self_message_iterator,
+        _config: *mut ffi::bt_self_message_iterator_configuration,
+        _port: *mut ffi::bt_self_component_port_output,
+    ) -> ffi::bt_message_iterator_class_initialize_method_status::Type {
+        use ffi::bt_message_iterator_class_initialize_method_status::*;
+
+        let mut msg_iter = SelfMessageIterator::from_raw(msg_iter);
+        let state = Box::<SourceIterState>::default();
+        msg_iter.set_c_user_data_ptr(Box::into_raw(state) as *mut c_void);
+        BT_MESSAGE_ITERATOR_CLASS_INITIALIZE_METHOD_STATUS_OK
+    }
+
+    pub extern "C" fn msg_iter_finalize(msg_iter: *mut ffi::bt_self_message_iterator) {
+        let mut msg_iter = SelfMessageIterator::from_raw(msg_iter);
+        let state = msg_iter.get_c_user_data_ptr() as *mut SourceIterState;
+        if !state.is_null() {
+            unsafe { drop(Box::from_raw(state)) };
+            msg_iter.set_c_user_data_ptr(std::ptr::null_mut());
+        }
+    }
+
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub extern "C" fn msg_iter_next(
+        msg_iter: *mut ffi::bt_self_message_iterator,
+        messages: ffi::bt_message_array_const,
+        capacity: u64,
+        count: *mut u64,
+    ) -> ffi::bt_message_iterator_class_next_method_status::Type {
+        use ffi::bt_message_iterator_class_next_method_status::*;
+
+        let mut msg_iter = SelfMessageIterator::from_raw(msg_iter);
+        let state = msg_iter.get_c_user_data_ptr() as *mut SourceIterState;
+        let mut component: SelfComponentSource = msg_iter.borrow_component();
+        let source = component.get_c_user_data_ptr() as *mut S;
+        if state.is_null() || source.is_null() {
+            log::error!("Source message iterator state is NULL");
+            return BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_ERROR;
+        }
+        let (state, source) = unsafe { (&mut (*state), &mut (*source)) };
+
+        if state.output.is_empty() && !state.ended {
+            match catch_panic(|| source.next(&mut msg_iter, &mut state.output)) {
+                Ok(status) => state.ended = status == NextStatus::End,
+                Err(e) => {
+                    log::error!("Source message iterator failed. {}", e);
+                    e.append_to_current_thread(file!(), line!());
+                    return BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_ERROR;
+                }
+            }
+        }
+
+        if !state.output.is_empty() {
+            let n = state.output.len().min(capacity as usize);
+            for (idx, msg) in state.output.drain(..n).enumerate() {
+                unsafe { *messages.add(idx) = msg.into_raw() };
+            }
+            unsafe { *count = n as u64 };
+            BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_OK
+        } else if state.ended {
+            BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_END
+        } else {
+            // Nothing to emit yet, give the graph a chance to run other components
+            BT_MESSAGE_ITERATOR_CLASS_NEXT_METHOD_STATUS_AGAIN
+        }
+    }
+}
+
+/// Generate the plugin and source component class descriptors for a `Source`
+/// implementation, in a module named `$module`.
+///
+/// The plugin is registered by passing the module to `register_static_plugins!`.
+#[macro_export]
+macro_rules! source_plugin_descriptors {
+    ($module:ident, $plugin_name:literal, $source_name:literal, $source:ty) => {
+        pub mod $module {
+            use super::*;
+            use $crate::ffi::*;
+
+            pub const PLUGIN_NAME: &[u8] = concat!($plugin_name, "\0").as_bytes();
+            pub const SOURCE_COMP_NAME: &[u8] = concat!($source_name, "\0").as_bytes();
+
+            pub static PLUGIN_DESC: __bt_plugin_descriptor = __bt_plugin_descriptor {
+                name: PLUGIN_NAME.as_ptr() as *const _,
+            };
+
+            pub static SOURCE_COMP_DESC: __bt_plugin_component_class_descriptor =
+                __bt_plugin_component_class_descriptor {
+                    plugin_descriptor: &PLUGIN_DESC,
+                    name: SOURCE_COMP_NAME.as_ptr() as *const _,
+                    type_: bt_component_class_type::BT_COMPONENT_CLASS_TYPE_SOURCE,
+                    methods: __bt_plugin_component_class_descriptor__bindgen_ty_1 {
+                        source: __bt_plugin_component_class_descriptor__bindgen_ty_1__bindgen_ty_1 {
+                            msg_iter_next: Some($crate::SourceMethods::<$source>::msg_iter_next),
+                        },
+                    },
+                };
+
+            pub static SOURCE_COMP_CLASS_INIT_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &SOURCE_COMP_DESC,
+                    type_name: b"source_initialize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_INITIALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        source_initialize_method: Some($crate::SourceMethods::<$source>::initialize),
+                    },
+                };
+
+            pub static SOURCE_COMP_CLASS_FINI_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &SOURCE_COMP_DESC,
+                    type_name: b"source_finalize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_FINALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        source_finalize_method: Some($crate::SourceMethods::<$source>::finalize),
+                    },
+                };
+
+            pub static SOURCE_COMP_CLASS_MSG_ITER_INIT_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &SOURCE_COMP_DESC,
+                    type_name: b"msg_iter_initialize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_MSG_ITER_INITIALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        msg_iter_initialize_method: Some($crate::SourceMethods::<$source>::msg_iter_initialize),
+                    },
+                };
+
+            pub static SOURCE_COMP_CLASS_MSG_ITER_FINI_ATTR: __bt_plugin_component_class_descriptor_attribute =
+                __bt_plugin_component_class_descriptor_attribute {
+                    comp_class_descriptor: &SOURCE_COMP_DESC,
+                    type_name: b"msg_iter_finalize_method\0".as_ptr() as *const _,
+                    type_: __bt_plugin_component_class_descriptor_attribute_type::BT_PLUGIN_COMPONENT_CLASS_DESCRIPTOR_ATTRIBUTE_TYPE_MSG_ITER_FINALIZE_METHOD,
+                    value: __bt_plugin_component_class_descriptor_attribute__bindgen_ty_1 {
+                        msg_iter_finalize_method: Some($crate::SourceMethods::<$source>::msg_iter_finalize),
+                    },
+                };
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    struct EmptySource;
+
+    impl Source for EmptySource {
+        fn initialize(_: &mut SelfComponentSource, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+            Ok(EmptySource)
+        }
+
+        fn next(
+            &mut self,
+            _: &mut SelfMessageIterator,
+            _: &mut VecDeque<Message>,
+        ) -> BtResult<NextStatus> {
+            Ok(NextStatus::End)
+        }
+    }
+
+    #[allow(dead_code)]
+    mod descriptors {
+        use super::*;
+        crate::source_plugin_descriptors!(empty_source_descriptors, "empty", "source", EmptySource);
+    }
+    use descriptors::empty_source_descriptors;
+
+    #[test]
+    fn cstrings_are_valid() {
+        let plugin = CStr::from_bytes_with_nul(empty_source_descriptors::PLUGIN_NAME).unwrap();
+        let source = CStr::from_bytes_with_nul(empty_source_descriptors::SOURCE_COMP_NAME).unwrap();
+        assert_eq!(plugin.to_str().unwrap(), "empty");
+        assert_eq!(source.to_str().unwrap(), "source");
+    }
+}
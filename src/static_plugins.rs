@@ -0,0 +1,136 @@
+/// Register the statically linked plugins with libbabeltrace2.
+///
+/// libbabeltrace2 discovers static plugins through descriptor pointers placed in
+/// the `__bt_plugin_*` linker sections of the final binary. Those sections don't
+/// survive when they only live in this crate's rlib
+/// (https://github.com/rust-lang/rust/issues/47384), so this macro must be
+/// invoked once at the top level of the binary crate. It registers the proxy,
+/// ctf and utils plugins, which `CtfIterator` and friends depend on.
+///
+/// Plugins generated by `sink_plugin_descriptors!`, `filter_plugin_descriptors!`
+/// and `source_plugin_descriptors!` are registered by passing their kind and
+/// module path:
+///
+/// ```ignore
+/// babeltrace2_sys::register_static_plugins!(sink my_sink_descriptors, filter my_filter_descriptors);
+/// ```
+///
+/// Loading a plugin that wasn't registered fails with `Error::StaticPluginNotRegistered`.
+#[macro_export]
+macro_rules! register_static_plugins {
+    ($($kind:ident $($module:ident)::+),* $(,)?) => {
+        const _: () = {
+            use $crate::ffi::*;
+            use $crate::proxy_plugin_descriptors::*;
+
+            #[used]
+            #[link_section = "__bt_plugin_descriptors"]
+            static PLUGIN_DESC_PTR: __bt_plugin_descriptor_ptr =
+                __bt_plugin_descriptor_ptr(&PLUGIN_DESC);
+
+            #[used]
+            #[link_section = "__bt_plugin_component_class_descriptors"]
+            static SINK_COMP_DESC_PTR: __bt_plugin_component_class_descriptor_ptr =
+                __bt_plugin_component_class_descriptor_ptr(&SINK_COMP_DESC);
+
+            $crate::register_static_plugins!(
+                @attrs SINK_COMP_CLASS_INIT_ATTR,
+                SINK_COMP_CLASS_FINI_ATTR,
+                SINK_COMP_CLASS_GRAPH_CONF_ATTR
+            );
+        };
+
+        const _: () = {
+            use $crate::ffi::*;
+
+            #[link(
+                name = "babeltrace-plugin-utils",
+                kind = "static",
+                modifiers = "+whole-archive"
+            )]
+            extern "C" {
+                #[link_name = "__bt_plugin_descriptor_auto_ptr"]
+                static UTILS_PLUGIN_DESC_PTR: *const __bt_plugin_descriptor;
+            }
+
+            #[link(
+                name = "babeltrace-plugin-ctf",
+                kind = "static",
+                modifiers = "+whole-archive"
+            )]
+            extern "C" {
+                #[link_name = "__bt_plugin_descriptor_auto_ptr"]
+                static CTF_PLUGIN_DESC_PTR: *const __bt_plugin_descriptor;
+            }
+        };
+
+        $($crate::register_static_plugins!(@plugin $kind $($module)::+);)*
+    };
+
+    (@plugin sink $($module:ident)::+) => {
+        const _: () = {
+            use $crate::ffi::*;
+            use $($module)::+::*;
+
+            $crate::register_static_plugins!(@descs SINK_COMP_DESC);
+            $crate::register_static_plugins!(
+                @attrs SINK_COMP_CLASS_INIT_ATTR,
+                SINK_COMP_CLASS_FINI_ATTR,
+                SINK_COMP_CLASS_GRAPH_CONF_ATTR
+            );
+        };
+    };
+
+    (@plugin filter $($module:ident)::+) => {
+        const _: () = {
+            use $crate::ffi::*;
+            use $($module)::+::*;
+
+            $crate::register_static_plugins!(@descs FILTER_COMP_DESC);
+            $crate::register_static_plugins!(
+                @attrs FILTER_COMP_CLASS_INIT_ATTR,
+                FILTER_COMP_CLASS_FINI_ATTR,
+                FILTER_COMP_CLASS_MSG_ITER_INIT_ATTR,
+                FILTER_COMP_CLASS_MSG_ITER_FINI_ATTR
+            );
+        };
+    };
+
+    (@plugin source $($module:ident)::+) => {
+        const _: () = {
+            use $crate::ffi::*;
+            use $($module)::+::*;
+
+            $crate::register_static_plugins!(@descs SOURCE_COMP_DESC);
+            $crate::register_static_plugins!(
+                @attrs SOURCE_COMP_CLASS_INIT_ATTR,
+                SOURCE_COMP_CLASS_FINI_ATTR,
+                SOURCE_COMP_CLASS_MSG_ITER_INIT_ATTR,
+                SOURCE_COMP_CLASS_MSG_ITER_FINI_ATTR
+            );
+        };
+    };
+
+    (@descs $comp_desc:ident) => {
+        #[used]
+        #[link_section = "__bt_plugin_descriptors"]
+        static PLUGIN_DESC_PTR: __bt_plugin_descriptor_ptr =
+            __bt_plugin_descriptor_ptr(&PLUGIN_DESC);
+
+        #[used]
+        #[link_section = "__bt_plugin_component_class_descriptors"]
+        static COMP_DESC_PTR: __bt_plugin_component_class_descriptor_ptr =
+            __bt_plugin_component_class_descriptor_ptr(&$comp_desc);
+    };
+
+    (@attrs $($attr:ident),*) => {
+        $(
+            const _: () = {
+                #[used]
+                #[link_section = "__bt_plugin_component_class_descriptor_attributes"]
+                static ATTR_PTR: __bt_plugin_component_class_descriptor_attribute_ptr =
+                    __bt_plugin_component_class_descriptor_attribute_ptr(&$attr);
+            };
+        )*
+    };
+}
@@ -1,4 +1,8 @@
-use crate::{ffi, util, BtResult, ClockClassProperties, Error, Trace};
+use crate::{
+    ffi, util, BtResult, ClockClassProperties, Error, EventClass, EventClassSchema, EventId,
+    FieldClass, OwnedFieldClass, Trace, TraceIndex,
+};
+use std::collections::BTreeMap;
 
 pub struct Stream {
     pub(crate) inner: *const ffi::bt_stream,
@@ -12,7 +16,21 @@ impl Stream {
         let class = unsafe { ffi::bt_stream_borrow_class_const(self.inner) };
         let clock_class = unsafe { ffi::bt_stream_class_borrow_default_clock_class_const(class) };
         let clock = ClockClassProperties::from_raw(clock_class)?;
-        Ok(StreamProperties { id, name, clock })
+        Ok(StreamProperties {
+            trace_index: 0,
+            id,
+            name,
+            clock,
+        })
+    }
+
+    pub fn class(&self) -> BtResult<StreamClass> {
+        let class = unsafe { ffi::bt_stream_borrow_class_const(self.inner) };
+        if class.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(StreamClass { inner: class })
+        }
     }
 
     pub fn trace(&self) -> BtResult<Trace> {
@@ -25,11 +43,148 @@ impl Stream {
     }
 }
 
+/// An immutably borrowed packet
+pub struct Packet {
+    pub(crate) inner: *const ffi::bt_packet,
+}
+
+impl Packet {
+    pub(crate) fn class(&self) -> BtResult<StreamClass> {
+        self.stream()?.class()
+    }
+
+    pub fn stream(&self) -> BtResult<Stream> {
+        let stream = unsafe { ffi::bt_packet_borrow_stream_const(self.inner) };
+        if stream.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            Ok(Stream { inner: stream })
+        }
+    }
+}
+
 pub type StreamId = u64;
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StreamProperties {
+    /// Index of the trace the stream belongs to.
+    /// Assigned by the iterators, zero when converted on its own.
+    pub trace_index: TraceIndex,
     pub id: StreamId,
     pub name: Option<String>,
     pub clock: Option<ClockClassProperties>,
 }
+
+pub struct StreamClass {
+    pub(crate) inner: *const ffi::bt_stream_class,
+}
+
+impl StreamClass {
+    /// All the stream classes of the trace class the given trace belongs to
+    pub(crate) fn all_from_trace(trace: &Trace) -> Vec<StreamClass> {
+        let trace_class = unsafe { ffi::bt_trace_borrow_class_const(trace.inner) };
+        if trace_class.is_null() {
+            return Vec::new();
+        }
+        let count = unsafe { ffi::bt_trace_class_get_stream_class_count(trace_class) };
+        (0..count)
+            .map(|idx| unsafe {
+                ffi::bt_trace_class_borrow_stream_class_by_index_const(trace_class, idx)
+            })
+            .filter(|c| !c.is_null())
+            .map(|inner| StreamClass { inner })
+            .collect()
+    }
+
+    pub(crate) fn has_default_clock(&self) -> bool {
+        !unsafe { ffi::bt_stream_class_borrow_default_clock_class_const(self.inner) }.is_null()
+    }
+
+    pub(crate) fn supports_packets(&self) -> bool {
+        unsafe { ffi::bt_stream_class_supports_packets(self.inner) != 0 }
+    }
+
+    pub(crate) fn packets_have_beginning_clock_snapshot(&self) -> bool {
+        unsafe {
+            ffi::bt_stream_class_packets_have_beginning_default_clock_snapshot(self.inner) != 0
+        }
+    }
+
+    pub(crate) fn packets_have_end_clock_snapshot(&self) -> bool {
+        unsafe { ffi::bt_stream_class_packets_have_end_default_clock_snapshot(self.inner) != 0 }
+    }
+
+    pub fn properties(&self) -> BtResult<StreamClassProperties> {
+        let c = self.inner;
+        let id = unsafe { ffi::bt_stream_class_get_id(c) };
+        let name = util::opt_owned_cstr(unsafe { ffi::bt_stream_class_get_name(c) })?;
+        let clock = ClockClassProperties::from_raw(unsafe {
+            ffi::bt_stream_class_borrow_default_clock_class_const(c)
+        })?;
+        let packet_context = FieldClass::opt_owned_from_raw(unsafe {
+            ffi::bt_stream_class_borrow_packet_context_field_class_const(c)
+        })?;
+        let event_common_context = FieldClass::opt_owned_from_raw(unsafe {
+            ffi::bt_stream_class_borrow_event_common_context_field_class_const(c)
+        })?;
+        let mut event_classes = BTreeMap::new();
+        let count = unsafe { ffi::bt_stream_class_get_event_class_count(c) };
+        for idx in 0..count {
+            let ec = unsafe { ffi::bt_stream_class_borrow_event_class_by_index_const(c, idx) };
+            if ec.is_null() {
+                return Err(Error::ResourceBorrow);
+            }
+            let schema = EventClass { inner: ec }.schema()?;
+            event_classes.insert(schema.properties.id, schema);
+        }
+        Ok(StreamClassProperties {
+            id,
+            name,
+            clock,
+            supports_packets: unsafe { ffi::bt_stream_class_supports_packets(c) } != 0,
+            packets_have_beginning_clock_snapshot: unsafe {
+                ffi::bt_stream_class_packets_have_beginning_default_clock_snapshot(c)
+            } != 0,
+            packets_have_end_clock_snapshot: unsafe {
+                ffi::bt_stream_class_packets_have_end_default_clock_snapshot(c)
+            } != 0,
+            supports_discarded_events: unsafe { ffi::bt_stream_class_supports_discarded_events(c) }
+                != 0,
+            discarded_events_have_clock_snapshots: unsafe {
+                ffi::bt_stream_class_discarded_events_have_default_clock_snapshots(c)
+            } != 0,
+            supports_discarded_packets: unsafe {
+                ffi::bt_stream_class_supports_discarded_packets(c)
+            } != 0,
+            discarded_packets_have_clock_snapshots: unsafe {
+                ffi::bt_stream_class_discarded_packets_have_default_clock_snapshots(c)
+            } != 0,
+            packet_context,
+            event_common_context,
+            event_classes,
+        })
+    }
+}
+
+pub type StreamClassId = u64;
+
+/// Description of a stream class and all of its event classes
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamClassProperties {
+    pub id: StreamClassId,
+    pub name: Option<String>,
+    /// Default clock class
+    pub clock: Option<ClockClassProperties>,
+    pub supports_packets: bool,
+    pub packets_have_beginning_clock_snapshot: bool,
+    pub packets_have_end_clock_snapshot: bool,
+    pub supports_discarded_events: bool,
+    pub discarded_events_have_clock_snapshots: bool,
+    pub supports_discarded_packets: bool,
+    pub discarded_packets_have_clock_snapshots: bool,
+    pub packet_context: Option<OwnedFieldClass>,
+    pub event_common_context: Option<OwnedFieldClass>,
+    pub event_classes: BTreeMap<EventId, EventClassSchema>,
+}
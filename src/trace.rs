@@ -4,6 +4,9 @@ use std::convert::TryInto;
 use std::{ptr, slice};
 use uuid::Uuid;
 
+/// Index of a trace, in the order the trace was first encountered in a pipeline
+pub type TraceIndex = usize;
+
 pub struct Trace {
     pub(crate) inner: *const ffi::bt_trace,
 }
@@ -72,8 +75,38 @@ impl Trace {
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TraceProperties {
     pub name: Option<String>,
     pub uuid: Option<Uuid>,
     pub env: Option<Env>,
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let props = TraceProperties {
+            name: Some("my-trace".to_owned()),
+            uuid: Some(Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef)),
+            env: Some(Env {
+                entries: [
+                    ("hostname".to_owned(), EnvValue::String("node0".to_owned())),
+                    ("tracer_major".to_owned(), EnvValue::Integer(2)),
+                ]
+                .into(),
+            }),
+        };
+        let json = serde_json::to_string(&props).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"my-trace","uuid":"01234567-89ab-cdef-0123-456789abcdef","env":{"hostname":{"String":"node0"},"tracer_major":{"Integer":2}}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<TraceProperties>(&json).unwrap(),
+            props
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{
+    ffi, BtResult, BtResultExt, ClockClass, Error, EventClass, OwnedField, OwnedFieldClass, Packet,
+    SelfComponentSource, Stream, StreamClass, Trace,
+};
+use std::ffi::CString;
+
+/// A trace class created by a source component, a reference is held on it.
+///
+/// A reference is also held on each of the stream and event classes it creates,
+/// they stay valid after the trace class is dropped.
+pub struct TraceClass {
+    inner: *mut ffi::bt_trace_class,
+}
+
+/// Stream class creation parameters, stream class IDs are assigned automatically
+#[derive(Default)]
+pub struct StreamClassParams<'a> {
+    pub name: Option<&'a str>,
+    pub clock: Option<&'a ClockClass>,
+    pub supports_packets: bool,
+    /// Whether packet beginning and end messages have clock snapshots,
+    /// requires a clock and packet support
+    pub packets_have_clock_snapshots: bool,
+    pub packet_context: Option<&'a OwnedFieldClass>,
+    pub event_common_context: Option<&'a OwnedFieldClass>,
+}
+
+impl TraceClass {
+    pub fn new(component: &mut SelfComponentSource) -> BtResult<Self> {
+        let inner = unsafe { ffi::bt_trace_class_create(component.inner as *mut _) };
+        if inner.is_null() {
+            Err(Error::Memory)
+        } else {
+            Ok(TraceClass { inner })
+        }
+    }
+
+    pub fn create_stream_class(
+        &mut self,
+        params: &StreamClassParams,
+    ) -> BtResult<StreamClassHandle> {
+        if params.packets_have_clock_snapshots && params.clock.is_none() {
+            return Err(Error::PacketClockSnapshotsRequireClock);
+        }
+        if params.packets_have_clock_snapshots && !params.supports_packets {
+            return Err(Error::PacketClockSnapshotsRequirePackets);
+        }
+        let sc = unsafe { ffi::bt_stream_class_create(self.inner) };
+        if sc.is_null() {
+            return Err(Error::Memory);
+        }
+        let handle = StreamClassHandle { inner: sc };
+
+        unsafe {
+            if let Some(name) = params.name {
+                let name = CString::new(name)?;
+                ffi::bt_stream_class_set_name(sc, name.as_ptr()).capi_result()?;
+            }
+            if let Some(clock) = params.clock {
+                ffi::bt_stream_class_set_default_clock_class(sc, clock.inner);
+            }
+            let with_cs = params.packets_have_clock_snapshots as _;
+            ffi::bt_stream_class_set_supports_packets(
+                sc,
+                params.supports_packets as _,
+                with_cs,
+                with_cs,
+            );
+            if let Some(fc) = params.packet_context {
+                let fc = fc.create(self.inner)?;
+                ffi::bt_stream_class_set_packet_context_field_class(sc, fc.0).capi_result()?;
+            }
+            if let Some(fc) = params.event_common_context {
+                let fc = fc.create(self.inner)?;
+                ffi::bt_stream_class_set_event_common_context_field_class(sc, fc.0)
+                    .capi_result()?;
+            }
+        }
+        Ok(handle)
+    }
+
+    /// Create an event class in `stream_class`, which must belong to this trace class
+    pub fn create_event_class(
+        &mut self,
+        stream_class: &StreamClassHandle,
+        name: &str,
+        specific_context: Option<&OwnedFieldClass>,
+        payload: Option<&OwnedFieldClass>,
+    ) -> BtResult<EventClassHandle> {
+        let ec = unsafe { ffi::bt_event_class_create(stream_class.inner) };
+        if ec.is_null() {
+            return Err(Error::Memory);
+        }
+        let handle = EventClassHandle { inner: ec };
+
+        let name = CString::new(name)?;
+        unsafe {
+            ffi::bt_event_class_set_name(ec, name.as_ptr()).capi_result()?;
+            if let Some(fc) = specific_context {
+                let fc = fc.create(self.inner)?;
+                ffi::bt_event_class_set_specific_context_field_class(ec, fc.0).capi_result()?;
+            }
+            if let Some(fc) = payload {
+                let fc = fc.create(self.inner)?;
+                ffi::bt_event_class_set_payload_field_class(ec, fc.0).capi_result()?;
+            }
+        }
+        Ok(handle)
+    }
+
+    pub fn create_trace(&mut self, name: Option<&str>) -> BtResult<TraceHandle> {
+        let inner = unsafe { ffi::bt_trace_create(self.inner) };
+        if inner.is_null() {
+            return Err(Error::Memory);
+        }
+        let trace = TraceHandle { inner };
+        if let Some(name) = name {
+            let name = CString::new(name)?;
+            unsafe { ffi::bt_trace_set_name(inner, name.as_ptr()) }.capi_result()?;
+        }
+        Ok(trace)
+    }
+}
+
+impl Drop for TraceClass {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_trace_class_put_ref(self.inner) };
+    }
+}
+
+/// A trace created by a source component, a reference is held on it.
+///
+/// A reference is also held on each of the streams it creates.
+pub struct TraceHandle {
+    inner: *mut ffi::bt_trace,
+}
+
+impl TraceHandle {
+    pub fn trace(&self) -> Trace {
+        Trace { inner: self.inner }
+    }
+
+    pub fn set_environment_entry(&mut self, name: &str, value: &str) -> BtResult<()> {
+        let name = CString::new(name)?;
+        let value = CString::new(value)?;
+        unsafe {
+            ffi::bt_trace_set_environment_entry_string(self.inner, name.as_ptr(), value.as_ptr())
+        }
+        .capi_result()
+    }
+
+    /// Create a stream of `stream_class`, which must belong to this trace's class
+    pub fn create_stream(&mut self, stream_class: &StreamClassHandle) -> BtResult<StreamHandle> {
+        let inner = unsafe { ffi::bt_stream_create(stream_class.inner, self.inner) };
+        if inner.is_null() {
+            Err(Error::Memory)
+        } else {
+            Ok(StreamHandle { inner })
+        }
+    }
+}
+
+impl Drop for TraceHandle {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_trace_put_ref(self.inner) };
+    }
+}
+
+/// A stream class created by a source component, a reference is held on it
+pub struct StreamClassHandle {
+    inner: *mut ffi::bt_stream_class,
+}
+
+impl StreamClassHandle {
+    pub fn stream_class(&self) -> StreamClass {
+        StreamClass { inner: self.inner }
+    }
+}
+
+impl Drop for StreamClassHandle {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_stream_class_put_ref(self.inner) };
+    }
+}
+
+/// An event class created by a source component, a reference is held on it
+pub struct EventClassHandle {
+    inner: *mut ffi::bt_event_class,
+}
+
+impl EventClassHandle {
+    pub fn event_class(&self) -> EventClass {
+        EventClass { inner: self.inner }
+    }
+}
+
+impl Drop for EventClassHandle {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_event_class_put_ref(self.inner) };
+    }
+}
+
+/// A stream created by a source component, a reference is held on it
+pub struct StreamHandle {
+    inner: *mut ffi::bt_stream,
+}
+
+impl StreamHandle {
+    pub fn stream(&self) -> Stream {
+        Stream { inner: self.inner }
+    }
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_stream_put_ref(self.inner) };
+    }
+}
+
+/// A packet created by a source component, a reference is held on it
+/// until the handle is dropped.
+pub struct PacketHandle {
+    inner: *mut ffi::bt_packet,
+}
+
+impl PacketHandle {
+    /// Create a packet in `stream`, its stream class must support packets
+    pub fn new(stream: &Stream) -> BtResult<Self> {
+        if !stream.class()?.supports_packets() {
+            return Err(Error::PacketsNotSupported);
+        }
+        let inner = unsafe { ffi::bt_packet_create(stream.inner) };
+        if inner.is_null() {
+            Err(Error::Memory)
+        } else {
+            Ok(PacketHandle { inner })
+        }
+    }
+
+    pub fn packet(&self) -> Packet {
+        Packet { inner: self.inner }
+    }
+
+    /// Set the packet context field, before the packet beginning message is created
+    pub fn set_context(&mut self, context: &OwnedField) -> BtResult<()> {
+        let field = unsafe { ffi::bt_packet_borrow_context_field(self.inner) };
+        if field.is_null() {
+            Err(Error::ResourceBorrow)
+        } else {
+            context.write_to(field)
+        }
+    }
+}
+
+impl Drop for PacketHandle {
+    fn drop(&mut self) {
+        unsafe { ffi::bt_packet_put_ref(self.inner) };
+    }
+}
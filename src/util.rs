@@ -1,6 +1,33 @@
-use crate::BtResult;
+use crate::{BtResult, Error};
 use std::ffi::CStr;
 use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Borrow a C string as `&str`, None if it's null or isn't valid UTF-8
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a C string that outlives `'a`.
+pub(crate) unsafe fn borrowed_cstr<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+/// Run a method of a custom component, a panic becomes an error instead of
+/// unwinding into libbabeltrace2, which aborts the process
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> BtResult<T>) -> BtResult<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let msg = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(Error::ComponentPanic(msg))
+    })
+}
 
 pub(crate) fn opt_owned_cstr(ptr: *const c_char) -> BtResult<Option<String>> {
     if ptr.is_null() {
@@ -14,3 +41,21 @@ pub(crate) fn opt_owned_cstr(ptr: *const c_char) -> BtResult<Option<String>> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catch_panic_as_error() {
+        assert_eq!(catch_panic(|| Ok(1)), Ok(1));
+        assert_eq!(
+            catch_panic::<()>(|| panic!("boom")),
+            Err(Error::ComponentPanic("boom".to_owned()))
+        );
+        assert_eq!(
+            catch_panic::<()>(|| panic!("{} boom", 2)),
+            Err(Error::ComponentPanic("2 boom".to_owned()))
+        );
+    }
+}
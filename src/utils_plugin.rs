@@ -1,24 +1,35 @@
-use crate::{BtResult, ComponentClassFilter, Plugin};
-use std::ffi::CStr;
+use crate::{
+    BtResult, ClockNanoseconds, ComponentClassFilter, ComponentLogLevel, Error, LoggingLevel,
+    Plugin, Value,
+};
+use std::ffi::{CStr, CString};
 
 /// See <https://babeltrace.org/docs/v2.0/man7/babeltrace2-filter.utils.muxer.7/>
+/// and <https://babeltrace.org/docs/v2.0/man7/babeltrace2-filter.utils.trimmer.7/>
 pub struct UtilsPlugin(Plugin);
 
 impl UtilsPlugin {
     pub const PLUGIN_NAME: &'static [u8] = b"utils\0";
     pub const MUXER_COMP_NAME: &'static [u8] = b"muxer\0";
     pub const GRAPH_NODE_NAME: &'static [u8] = b"filter.utils.muxer\0";
+    pub const TRIMMER_COMP_NAME: &'static [u8] = b"trimmer\0";
+    pub const TRIMMER_GRAPH_NODE_NAME: &'static [u8] = b"filter.utils.trimmer\0";
 
     pub fn load() -> BtResult<Self> {
         let name = Self::plugin_name();
         Ok(UtilsPlugin(Plugin::load_from_statics_by_name(name)?))
     }
 
-    pub fn borrow_muxer_filter_component_class(&self) -> BtResult<ComponentClassFilter> {
+    pub fn borrow_muxer_filter_component_class(&self) -> BtResult<ComponentClassFilter<'_>> {
         let name = Self::muxer_name();
         self.0.borrow_filter_component_class_by_name(name)
     }
 
+    pub fn borrow_trimmer_filter_component_class(&self) -> BtResult<ComponentClassFilter<'_>> {
+        let name = Self::trimmer_name();
+        self.0.borrow_filter_component_class_by_name(name)
+    }
+
     pub fn plugin_name() -> &'static CStr {
         unsafe { CStr::from_bytes_with_nul_unchecked(Self::PLUGIN_NAME) }
     }
@@ -30,6 +41,132 @@ impl UtilsPlugin {
     pub fn graph_node_name() -> &'static CStr {
         unsafe { CStr::from_bytes_with_nul_unchecked(Self::GRAPH_NODE_NAME) }
     }
+
+    pub fn trimmer_name() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::TRIMMER_COMP_NAME) }
+    }
+
+    pub fn trimmer_graph_node_name() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::TRIMMER_GRAPH_NODE_NAME) }
+    }
+}
+
+/// A bound of the trimmer's time range
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum TrimmerBound {
+    /// Nanoseconds from the clock's origin
+    NsFromOrigin(ClockNanoseconds),
+    /// A time string, `YYYY-MM-DD HH:MM[:SS[.NANO]]`, `HH:MM[:SS[.NANO]]`
+    /// or `[-]SEC[.NANO]`
+    Time(String),
+}
+
+impl TrimmerBound {
+    fn to_param_string(&self) -> String {
+        match self {
+            TrimmerBound::NsFromOrigin(ns) => {
+                let sign = if *ns < 0 { "-" } else { "" };
+                let abs = ns.unsigned_abs();
+                format!("{}{}.{:09}", sign, abs / 1_000_000_000, abs % 1_000_000_000)
+            }
+            TrimmerBound::Time(t) => t.clone(),
+        }
+    }
+}
+
+/// See <https://babeltrace.org/docs/v2.0/man7/babeltrace2-filter.utils.trimmer.7/#doc-_initialization_parameters>
+pub struct UtilsPluginFilterTrimmerInitParams {
+    params: Value,
+    log_level: Option<LoggingLevel>,
+    _begin_val: Option<Value>,
+    _end_val: Option<Value>,
+    _gmt_val: Option<Value>,
+}
+
+impl UtilsPluginFilterTrimmerInitParams {
+    pub const BEGIN_KEY: &'static [u8] = b"begin\0";
+    pub const END_KEY: &'static [u8] = b"end\0";
+    pub const GMT_KEY: &'static [u8] = b"gmt\0";
+
+    pub fn new(
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-filter.utils.trimmer.7/#doc-param-begin
+        begin: Option<TrimmerBound>,
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-filter.utils.trimmer.7/#doc-param-end
+        end: Option<TrimmerBound>,
+        // https://babeltrace.org/docs/v2.0/man7/babeltrace2-filter.utils.trimmer.7/#doc-param-gmt
+        gmt: Option<bool>,
+    ) -> BtResult<Self> {
+        log::debug!(
+            "Creating filter.utils.trimmer init params: begin={:?}, end={:?}, gmt={:?}",
+            begin,
+            end,
+            gmt,
+        );
+
+        if begin.is_none() && end.is_none() {
+            return Err(Error::TrimmerRequiresBound);
+        }
+
+        let mut params = Value::new_map()?;
+
+        let begin_val = if let Some(begin) = begin {
+            let val = Value::new_string_with(&CString::new(begin.to_param_string())?)?;
+            params.insert_entry(Self::begin_key(), &val)?;
+            val.into()
+        } else {
+            None
+        };
+
+        let end_val = if let Some(end) = end {
+            let val = Value::new_string_with(&CString::new(end.to_param_string())?)?;
+            params.insert_entry(Self::end_key(), &val)?;
+            val.into()
+        } else {
+            None
+        };
+
+        let gmt_val = if let Some(gmt) = gmt {
+            let val = Value::new_bool_with(gmt)?;
+            params.insert_entry(Self::gmt_key(), &val)?;
+            val.into()
+        } else {
+            None
+        };
+
+        Ok(UtilsPluginFilterTrimmerInitParams {
+            params,
+            log_level: None,
+            _begin_val: begin_val,
+            _end_val: end_val,
+            _gmt_val: gmt_val,
+        })
+    }
+
+    pub fn params(&self) -> &Value {
+        &self.params
+    }
+
+    fn begin_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::BEGIN_KEY) }
+    }
+
+    fn end_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::END_KEY) }
+    }
+
+    fn gmt_key() -> &'static CStr {
+        unsafe { CStr::from_bytes_with_nul_unchecked(Self::GMT_KEY) }
+    }
+}
+
+impl ComponentLogLevel for UtilsPluginFilterTrimmerInitParams {
+    fn log_level(&self) -> Option<LoggingLevel> {
+        self.log_level
+    }
+
+    fn set_log_level(&mut self, log_level: Option<LoggingLevel>) {
+        self.log_level = log_level;
+    }
 }
 
 #[cfg(test)]
@@ -41,5 +178,50 @@ mod tests {
         assert_ne!(UtilsPlugin::plugin_name().to_str().unwrap().len(), 0);
         assert_ne!(UtilsPlugin::muxer_name().to_str().unwrap().len(), 0);
         assert_ne!(UtilsPlugin::graph_node_name().to_str().unwrap().len(), 0);
+        assert_ne!(UtilsPlugin::trimmer_name().to_str().unwrap().len(), 0);
+        assert_ne!(
+            UtilsPlugin::trimmer_graph_node_name()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_ne!(
+            UtilsPluginFilterTrimmerInitParams::begin_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_ne!(
+            UtilsPluginFilterTrimmerInitParams::end_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert_ne!(
+            UtilsPluginFilterTrimmerInitParams::gmt_key()
+                .to_str()
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn trimmer_bound_strings() {
+        assert_eq!(
+            TrimmerBound::NsFromOrigin(1_500_000_000).to_param_string(),
+            "1.500000000"
+        );
+        assert_eq!(
+            TrimmerBound::NsFromOrigin(-5).to_param_string(),
+            "-0.000000005"
+        );
+        assert_eq!(
+            TrimmerBound::Time("2021-01-01 12:00".to_owned()).to_param_string(),
+            "2021-01-01 12:00"
+        );
     }
 }
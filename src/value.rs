@@ -1,74 +1,152 @@
-use crate::{ffi, BtResult, BtResultExt, Error};
+use crate::{ffi, util, BtResult, BtResultExt, Error};
+use std::cell::Cell;
 use std::ffi::CStr;
+use std::marker::PhantomData;
 
 /// Generic, JSON-like basic data containers
 pub struct Value {
     pub(crate) inner: *mut ffi::bt_value,
+    frozen: Cell<bool>,
 }
 
 impl Value {
-    pub(crate) fn new_map() -> BtResult<Self> {
-        let inner = unsafe { ffi::bt_value_map_create() };
+    fn from_raw_created(inner: *mut ffi::bt_value) -> BtResult<Self> {
         if inner.is_null() {
             Err(Error::Memory)
         } else {
-            Ok(Value { inner })
+            Ok(Value {
+                inner,
+                frozen: Cell::new(false),
+            })
         }
     }
 
-    pub(crate) fn new_array() -> BtResult<Self> {
-        let inner = unsafe { ffi::bt_value_array_create() };
-        if inner.is_null() {
-            Err(Error::Memory)
+    /// libbabeltrace2 freezes the parameters of a component when it's added to a graph
+    pub(crate) fn mark_frozen(&self) {
+        self.frozen.set(true);
+    }
+
+    /// Returns true when the value can no longer be modified, i.e. once it was
+    /// given to a component or inserted in a container
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.get()
+    }
+
+    fn check_mutable(
+        &self,
+        typ: ffi::bt_value_type::Type,
+        type_name: &'static str,
+    ) -> BtResult<()> {
+        if self.is_frozen() {
+            Err(Error::ValueFrozen)
+        } else if !self.is_type(typ) {
+            Err(Error::ValueTypeMismatch(type_name))
         } else {
-            Ok(Value { inner })
+            Ok(())
         }
     }
 
-    pub(crate) fn new_string_with(value: &CStr) -> BtResult<Self> {
-        let inner = unsafe { ffi::bt_value_string_create_init(value.as_ptr()) };
-        if inner.is_null() {
-            Err(Error::Memory)
-        } else {
-            Ok(Value { inner })
+    fn is_type(&self, typ: ffi::bt_value_type::Type) -> bool {
+        self.as_value_ref().is_type(typ)
+    }
+
+    /// Borrow the value for reading
+    pub fn as_value_ref(&self) -> ValueRef<'_> {
+        ValueRef {
+            inner: self.inner,
+            _owner: PhantomData,
         }
     }
 
-    pub(crate) fn new_signed_int_with(value: i64) -> BtResult<Self> {
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_value_ref().as_bool()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_value_ref().as_u64()
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_value_ref().as_i64()
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_value_ref().as_f64()
+    }
+
+    pub fn as_string(&self) -> BtResult<Option<String>> {
+        self.as_value_ref().as_string()
+    }
+
+    /// Returns the entry of a map value
+    pub fn map_entry(&self, key: &CStr) -> Option<ValueRef<'_>> {
+        self.as_value_ref().map_entry(key)
+    }
+
+    /// Returns the elements of an array value
+    pub fn array_elements(&self) -> Vec<ValueRef<'_>> {
+        self.as_value_ref().array_elements()
+    }
+
+    pub fn new_map() -> BtResult<Self> {
+        let inner = unsafe { ffi::bt_value_map_create() };
+        Value::from_raw_created(inner)
+    }
+
+    pub fn new_array() -> BtResult<Self> {
+        let inner = unsafe { ffi::bt_value_array_create() };
+        Value::from_raw_created(inner)
+    }
+
+    pub fn new_string_with(value: &CStr) -> BtResult<Self> {
+        let inner = unsafe { ffi::bt_value_string_create_init(value.as_ptr()) };
+        Value::from_raw_created(inner)
+    }
+
+    pub fn new_signed_int_with(value: i64) -> BtResult<Self> {
         let inner = unsafe { ffi::bt_value_integer_signed_create_init(value) };
-        if inner.is_null() {
-            Err(Error::Memory)
-        } else {
-            Ok(Value { inner })
-        }
+        Value::from_raw_created(inner)
+    }
+
+    pub fn new_unsigned_int_with(value: u64) -> BtResult<Self> {
+        let inner = unsafe { ffi::bt_value_integer_unsigned_create_init(value) };
+        Value::from_raw_created(inner)
+    }
+
+    pub fn new_real_with(value: f64) -> BtResult<Self> {
+        let inner = unsafe { ffi::bt_value_real_create_init(value) };
+        Value::from_raw_created(inner)
     }
 
-    pub(crate) fn new_bool_with(value: bool) -> BtResult<Self> {
+    pub fn new_bool_with(value: bool) -> BtResult<Self> {
         let inner = unsafe { ffi::bt_value_bool_create_init(value as _) };
-        if inner.is_null() {
-            Err(Error::Memory)
-        } else {
-            Ok(Value { inner })
-        }
+        Value::from_raw_created(inner)
     }
 
-    pub(crate) fn insert_entry(&mut self, key: &CStr, value: &Value) -> BtResult<()> {
-        debug_assert_eq!(
-            unsafe { ffi::bt_value_get_type(self.inner) },
-            ffi::bt_value_type::BT_VALUE_TYPE_MAP
-        );
+    /// Insert `value` in a map value, `value` is shared with the map
+    /// so it can no longer be modified on its own afterwards
+    pub fn insert_entry(&mut self, key: &CStr, value: &Value) -> BtResult<()> {
+        self.check_mutable(ffi::bt_value_type::BT_VALUE_TYPE_MAP, "map")?;
         unsafe { ffi::bt_value_map_insert_entry(self.inner, key.as_ptr(), value.inner) }
-            .capi_result()
+            .capi_result()?;
+        value.mark_frozen();
+        Ok(())
     }
 
-    pub(crate) fn append_string_element(&mut self, value: &CStr) -> BtResult<()> {
-        debug_assert_eq!(
-            unsafe { ffi::bt_value_get_type(self.inner) },
-            ffi::bt_value_type::BT_VALUE_TYPE_ARRAY
-        );
+    pub fn append_string_element(&mut self, value: &CStr) -> BtResult<()> {
+        self.check_mutable(ffi::bt_value_type::BT_VALUE_TYPE_ARRAY, "array")?;
         unsafe { ffi::bt_value_array_append_string_element(self.inner, value.as_ptr()) }
             .capi_result()
     }
+
+    /// Append `value` to an array value, `value` is shared with the array
+    /// so it can no longer be modified on its own afterwards
+    pub fn append_element(&mut self, value: &Value) -> BtResult<()> {
+        self.check_mutable(ffi::bt_value_type::BT_VALUE_TYPE_ARRAY, "array")?;
+        unsafe { ffi::bt_value_array_append_element(self.inner, value.inner) }.capi_result()?;
+        value.mark_frozen();
+        Ok(())
+    }
 }
 
 impl Drop for Value {
@@ -76,3 +154,94 @@ impl Drop for Value {
         unsafe { ffi::bt_value_put_ref(self.inner) };
     }
 }
+
+/// A read-only borrowed value, e.g. the parameters of a component
+#[derive(Copy, Clone)]
+pub struct ValueRef<'a> {
+    inner: *const ffi::bt_value,
+    _owner: PhantomData<&'a ()>,
+}
+
+impl<'a> ValueRef<'a> {
+    pub(crate) fn from_raw(inner: *const ffi::bt_value) -> Option<Self> {
+        if inner.is_null() {
+            None
+        } else {
+            Some(ValueRef {
+                inner,
+                _owner: PhantomData,
+            })
+        }
+    }
+
+    fn is_type(self, typ: ffi::bt_value_type::Type) -> bool {
+        unsafe { ffi::bt_value_get_type(self.inner) == typ }
+    }
+
+    pub fn as_bool(self) -> Option<bool> {
+        if self.is_type(ffi::bt_value_type::BT_VALUE_TYPE_BOOL) {
+            Some(unsafe { ffi::bt_value_bool_get(self.inner) } != 0)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_u64(self) -> Option<u64> {
+        if self.is_type(ffi::bt_value_type::BT_VALUE_TYPE_UNSIGNED_INTEGER) {
+            Some(unsafe { ffi::bt_value_integer_unsigned_get(self.inner) })
+        } else {
+            None
+        }
+    }
+
+    pub fn as_i64(self) -> Option<i64> {
+        if self.is_type(ffi::bt_value_type::BT_VALUE_TYPE_SIGNED_INTEGER) {
+            Some(unsafe { ffi::bt_value_integer_signed_get(self.inner) })
+        } else {
+            None
+        }
+    }
+
+    pub fn as_f64(self) -> Option<f64> {
+        if self.is_type(ffi::bt_value_type::BT_VALUE_TYPE_REAL) {
+            Some(unsafe { ffi::bt_value_real_get(self.inner) })
+        } else {
+            None
+        }
+    }
+
+    pub fn as_string(self) -> BtResult<Option<String>> {
+        if self.is_type(ffi::bt_value_type::BT_VALUE_TYPE_STRING) {
+            util::opt_owned_cstr(unsafe { ffi::bt_value_string_get(self.inner) })
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the entry of a map value
+    pub fn map_entry(self, key: &CStr) -> Option<ValueRef<'a>> {
+        if self.is_type(ffi::bt_value_type::BT_VALUE_TYPE_MAP) {
+            ValueRef::from_raw(unsafe {
+                ffi::bt_value_map_borrow_entry_value_const(self.inner, key.as_ptr())
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the elements of an array value
+    pub fn array_elements(self) -> Vec<ValueRef<'a>> {
+        if self.is_type(ffi::bt_value_type::BT_VALUE_TYPE_ARRAY) {
+            let len = unsafe { ffi::bt_value_array_get_length(self.inner) };
+            (0..len)
+                .filter_map(|idx| {
+                    ValueRef::from_raw(unsafe {
+                        ffi::bt_value_array_borrow_element_by_index_const(self.inner, idx)
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// Not every test binary uses every fixture
+#![allow(dead_code)]
+
+use std::ffi::CString;
+use std::fs;
+use std::path::Path;
+
+pub fn init_logging() {
+    let _ = env_logger::builder().is_test(true).try_init();
+}
+
+/// A packet of the trace's only stream, see `METADATA`
+pub struct Packet {
+    pub seq_num: u32,
+    /// Cumulative number of discarded events at the end of the packet
+    pub discarded_events: u16,
+    /// Timestamp and `s` payload member of each `ev` event
+    pub events: Vec<(u32, &'static str)>,
+}
+
+impl Packet {
+    pub fn new(seq_num: u32, events: Vec<(u32, &'static str)>) -> Self {
+        Packet {
+            seq_num,
+            discarded_events: 0,
+            events,
+        }
+    }
+
+    pub fn with_discarded_events(mut self, discarded_events: u16) -> Self {
+        self.discarded_events = discarded_events;
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let begin = self.events.first().map(|e| e.0).unwrap_or(0) as u64;
+        let end = self.events.last().map(|e| e.0).unwrap_or(0) as u64;
+
+        let mut b = Vec::new();
+        // packet.header
+        b.extend_from_slice(&0xC1FC_1FC1_u32.to_le_bytes());
+        b.push(0);
+        // packet.context, sizes are patched below
+        align(&mut b, 2);
+        let size_offset = b.len();
+        b.extend_from_slice(&[0; 4]);
+        align(&mut b, 8);
+        b.extend_from_slice(&begin.to_le_bytes());
+        b.extend_from_slice(&end.to_le_bytes());
+        b.extend_from_slice(&self.discarded_events.to_le_bytes());
+        align(&mut b, 4);
+        b.extend_from_slice(&self.seq_num.to_le_bytes());
+        for (timestamp, s) in self.events.iter() {
+            // event.header
+            b.push(0);
+            align(&mut b, 4);
+            b.extend_from_slice(&timestamp.to_le_bytes());
+            // event.fields
+            b.extend_from_slice(CString::new(*s).unwrap().as_bytes_with_nul());
+        }
+
+        let size_bits = (b.len() * 8) as u16;
+        b[size_offset..size_offset + 2].copy_from_slice(&size_bits.to_le_bytes());
+        b[size_offset + 2..size_offset + 4].copy_from_slice(&size_bits.to_le_bytes());
+        b
+    }
+}
+
+fn align(b: &mut Vec<u8>, bytes: usize) {
+    let padding = (bytes - b.len() % bytes) % bytes;
+    b.resize(b.len() + padding, 0);
+}
+
+/// Write a CTF trace made of the metadata and a single data stream file
+pub fn write_trace(dir: &Path, packets: &[Packet]) {
+    let data: Vec<u8> = packets.iter().flat_map(Packet::to_bytes).collect();
+    write_trace_bytes(dir, &data);
+}
+
+/// Write a CTF trace with raw data stream bytes, e.g. to corrupt them
+pub fn write_trace_bytes(dir: &Path, data: &[u8]) {
+    fs::write(dir.join("metadata"), METADATA).unwrap();
+    fs::write(dir.join("stream"), data).unwrap();
+}
+
+pub const METADATA: &str = r#"/* CTF 1.8 */
+
+trace {
+	major = 1;
+	minor = 8;
+	byte_order = le;
+	packet.header := struct {
+		integer { signed = false; size = 32; align = 32; byte_order = native; base = 10; } magic;
+		integer { signed = false; size = 8; align = 8; byte_order = native; base = 10; } stream_id;
+	} align(8);
+};
+
+clock {
+	name = default;
+	freq = 1000000000;
+	precision = 0;
+	offset_s = 0;
+	offset = 0;
+	absolute = false;
+};
+
+stream {
+	id = 0;
+	packet.context := struct {
+		integer { signed = false; size = 16; align = 16; byte_order = native; base = 10; } packet_size;
+		integer { signed = false; size = 16; align = 16; byte_order = native; base = 10; } content_size;
+		integer { signed = false; size = 64; align = 64; byte_order = native; base = 10; map = clock.default.value; } timestamp_begin;
+		integer { signed = false; size = 64; align = 64; byte_order = native; base = 10; map = clock.default.value; } timestamp_end;
+		integer { signed = false; size = 16; align = 16; byte_order = native; base = 10; } events_discarded;
+		integer { signed = false; size = 32; align = 32; byte_order = native; base = 10; } packet_seq_num;
+	} align(8);
+	event.header := struct {
+		integer { signed = false; size = 8; align = 8; byte_order = native; base = 10; } id;
+		integer { signed = false; size = 32; align = 32; byte_order = native; base = 10; map = clock.default.value; } timestamp;
+	} align(8);
+};
+
+event {
+	stream_id = 0;
+	id = 0;
+	name = "ev";
+	fields := struct {
+		string { encoding = UTF8; } s;
+	} align(1);
+};"#;
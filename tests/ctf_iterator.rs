@@ -0,0 +1,320 @@
+#![deny(warnings, clippy::all)]
+
+use babeltrace2_sys::{
+    ComponentClassType, ComponentLogLevel, ComponentLoggingLevels, CtfItem, CtfItemRef,
+    CtfIterator, CtfPluginSourceFsInitParams, DiscardedKind, Error, ErrorCauseActor, FieldType,
+    LoggingLevel, OwnedFieldClass, PipelineLoggingLevels, StructureMemberClass,
+};
+use common::{init_logging, write_trace, write_trace_bytes, Packet};
+use std::ffi::CString;
+use std::path::Path;
+
+mod common;
+
+babeltrace2_sys::register_static_plugins!();
+
+fn fs_params(trace_dir: &Path) -> CtfPluginSourceFsInitParams {
+    let input = CString::new(trace_dir.to_str().unwrap()).unwrap();
+    CtfPluginSourceFsInitParams::new(None, None, None, None, &[input.as_c_str()]).unwrap()
+}
+
+fn packets() -> Vec<Packet> {
+    vec![
+        Packet::new(0, vec![(1, "a"), (2, "b")]),
+        Packet::new(1, vec![(3, "c")]),
+    ]
+}
+
+#[test]
+fn event_refs() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(td.path(), &packets());
+
+    let mut iter =
+        CtfIterator::new_with_event_refs(LoggingLevel::None, &fs_params(td.path())).unwrap();
+
+    let mut events = Vec::new();
+    while let Some(event) = iter.next_event_ref() {
+        let event = event.unwrap();
+        let class = event.class_properties().unwrap();
+        assert_eq!(class.name.as_deref(), Some("ev"));
+        let s = event
+            .payload()
+            .and_then(|p| p.member_by_name("s"))
+            .and_then(|s| s.as_str())
+            .unwrap();
+        let owned = event.to_owned().unwrap();
+        assert_eq!(owned.clock_snapshot, event.clock_snapshot());
+        assert_eq!(owned.properties.get("payload.s").unwrap().as_str(), Ok(s));
+        events.push((event.clock_snapshot().unwrap(), s.to_owned()));
+    }
+    assert_eq!(
+        events,
+        vec![
+            (1, "a".to_owned()),
+            (2, "b".to_owned()),
+            (3, "c".to_owned())
+        ]
+    );
+    assert!(iter.next_event_ref().is_none());
+}
+
+#[test]
+fn event_refs_not_enabled() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(td.path(), &packets());
+
+    let mut iter = CtfIterator::new(LoggingLevel::None, &fs_params(td.path())).unwrap();
+    assert!(matches!(
+        iter.next_event_ref(),
+        Some(Err(Error::EventRefsNotEnabled))
+    ));
+    assert!(iter.next_event_ref().is_none());
+
+    // Owned iteration is unaffected
+    assert_eq!(iter.count(), 3);
+}
+
+#[test]
+fn field_classes_to_owned() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(td.path(), &packets());
+
+    let payload_class = OwnedFieldClass::Structure(vec![StructureMemberClass {
+        name: Some("s".to_owned()),
+        class: OwnedFieldClass::String,
+    }]);
+
+    let mut iter =
+        CtfIterator::new_with_event_refs(LoggingLevel::None, &fs_params(td.path())).unwrap();
+    let stream_class = iter.stream_classes().values().next().unwrap();
+    let schema = stream_class.event_classes.get(&0).unwrap();
+    assert_eq!(schema.payload.as_ref(), Some(&payload_class));
+    assert_eq!(schema.specific_context, None);
+
+    let event = iter.next_event_ref().unwrap().unwrap();
+    let class = event.payload_class().unwrap();
+    assert_eq!(class.get_type(), FieldType::Structure);
+    assert_eq!(class.to_owned().unwrap(), payload_class);
+    assert!(event.specific_context_class().is_none());
+
+    let member = event.payload().unwrap().member_by_name("s").unwrap();
+    assert_eq!(member.class().get_type(), FieldType::String);
+    assert_eq!(member.class().to_owned().unwrap(), OwnedFieldClass::String);
+}
+
+#[test]
+fn discarded_events_in_order() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(
+        td.path(),
+        &[
+            Packet::new(0, vec![(1, "a"), (2, "b")]),
+            Packet::new(1, vec![(5, "c")]).with_discarded_events(2),
+            Packet::new(2, vec![(6, "d")]).with_discarded_events(2),
+        ],
+    );
+
+    let mut iter = CtfIterator::new(LoggingLevel::None, &fs_params(td.path())).unwrap();
+    let mut items = Vec::new();
+    while let Some(item) = iter.next_item() {
+        items.push(match item.unwrap() {
+            CtfItem::Event(e) => {
+                let s = e.properties.get("payload.s").unwrap().as_str().unwrap();
+                format!("event {}", s)
+            }
+            CtfItem::Discarded(d) => {
+                assert_eq!(d.kind, DiscardedKind::Events);
+                format!("discarded {:?}", d.count)
+            }
+        });
+    }
+    assert_eq!(
+        items,
+        vec![
+            "event a",
+            "event b",
+            "discarded Some(2)",
+            "event c",
+            "event d"
+        ]
+    );
+
+    // Borrowed items come in the same order
+    let mut iter =
+        CtfIterator::new_with_event_refs(LoggingLevel::None, &fs_params(td.path())).unwrap();
+    let mut kinds = Vec::new();
+    while let Some(item) = iter.next_item_ref() {
+        kinds.push(match item.unwrap() {
+            CtfItemRef::Event(_) => "event",
+            CtfItemRef::Discarded(_) => "discarded",
+        });
+    }
+    assert_eq!(kinds, vec!["event", "event", "discarded", "event", "event"]);
+
+    // Iterating over the events alone skips the notices
+    let iter = CtfIterator::new(LoggingLevel::None, &fs_params(td.path())).unwrap();
+    assert_eq!(iter.count(), 4);
+}
+
+#[test]
+fn event_ref_trace_index() {
+    init_logging();
+
+    let td_a = tempfile::tempdir().unwrap();
+    let td_b = tempfile::tempdir().unwrap();
+    write_trace(td_a.path(), &[Packet::new(0, vec![(1, "a"), (3, "a")])]);
+    write_trace(td_b.path(), &[Packet::new(0, vec![(2, "b"), (4, "b")])]);
+    let input_a = CString::new(td_a.path().to_str().unwrap()).unwrap();
+    let input_b = CString::new(td_b.path().to_str().unwrap()).unwrap();
+    let params = CtfPluginSourceFsInitParams::new(
+        None,
+        None,
+        None,
+        None,
+        &[input_a.as_c_str(), input_b.as_c_str()],
+    )
+    .unwrap();
+
+    let mut iter = CtfIterator::new_with_event_refs(LoggingLevel::None, &params).unwrap();
+    let mut indices = Vec::new();
+    while let Some(event) = iter.next_event_ref() {
+        let event = event.unwrap();
+        let s = event
+            .payload()
+            .and_then(|p| p.member_by_name("s"))
+            .and_then(|s| s.as_str())
+            .unwrap()
+            .to_owned();
+        assert_eq!(event.to_owned().unwrap().trace_index, event.trace_index());
+        indices.push((s, event.trace_index()));
+    }
+    assert_eq!(iter.trace_properties().len(), 2);
+    assert_eq!(indices.len(), 4);
+    let index_of = |name: &str| {
+        let mut idx = indices.iter().filter(|(s, _)| s == name).map(|(_, i)| *i);
+        let first = idx.next().unwrap();
+        assert!(idx.all(|i| i == first));
+        first
+    };
+    assert_ne!(index_of("a"), index_of("b"));
+}
+
+#[test]
+fn seek_after_end() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(td.path(), &packets());
+
+    let mut iter = CtfIterator::new(LoggingLevel::None, &fs_params(td.path())).unwrap();
+    let payloads = |iter: &mut CtfIterator| {
+        iter.by_ref()
+            .map(|e| {
+                let e = e.unwrap();
+                e.properties
+                    .get("payload.s")
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_owned()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(payloads(&mut iter), vec!["a", "b", "c"]);
+    assert!(iter.next().is_none());
+
+    iter.seek_beginning().unwrap();
+    assert_eq!(payloads(&mut iter), vec!["a", "b", "c"]);
+
+    iter.seek_ns_from_origin(2).unwrap();
+    assert_eq!(payloads(&mut iter), vec!["b", "c"]);
+
+    // Borrowed events too
+    let mut iter =
+        CtfIterator::new_with_event_refs(LoggingLevel::None, &fs_params(td.path())).unwrap();
+    while iter.next_event_ref().is_some() {}
+    iter.seek_beginning().unwrap();
+    let event = iter.next_event_ref().unwrap().unwrap();
+    assert_eq!(event.clock_snapshot(), Some(1));
+}
+
+#[test]
+fn corrupt_trace_error_causes() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    let mut data: Vec<u8> = packets().iter().flat_map(Packet::to_bytes).collect();
+    // Bad magic number
+    data[..4].copy_from_slice(&[0xAB; 4]);
+    write_trace_bytes(td.path(), &data);
+
+    let err = match CtfIterator::new(LoggingLevel::None, &fs_params(td.path())) {
+        Err(e) => e,
+        Ok(iter) => iter
+            .map(|e| e.map(|_| ()))
+            .find_map(Result::err)
+            .expect("Decoding a corrupt trace should fail"),
+    };
+    let root_cause = err
+        .causes()
+        .and_then(|c| c.root_cause())
+        .expect("The error should carry the libbabeltrace2 causes");
+    match &root_cause.actor {
+        ErrorCauseActor::Component {
+            class_type,
+            class_name,
+            plugin_name,
+            ..
+        } => {
+            assert_eq!(*class_type, ComponentClassType::Source);
+            assert_eq!(class_name, "fs");
+            assert_eq!(plugin_name.as_deref(), Some("ctf"));
+        }
+        actor => panic!("Unexpected root cause actor {:?}", actor),
+    }
+}
+
+#[test]
+fn component_logging_levels() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(td.path(), &[Packet::new(0, vec![(1, "a")])]);
+    let params = fs_params(td.path()).with_log_level(LoggingLevel::Warn);
+
+    let log_levels = PipelineLoggingLevels {
+        global: LoggingLevel::None,
+        muxer: Some(LoggingLevel::Info),
+        proxy_sink: Some(LoggingLevel::Error),
+    };
+    let iter = CtfIterator::new(log_levels, &params).unwrap();
+    assert_eq!(
+        iter.component_logging_levels(),
+        ComponentLoggingLevels {
+            source: LoggingLevel::Warn,
+            muxer: LoggingLevel::Info,
+            trimmer: None,
+            proxy_sink: LoggingLevel::Error,
+        }
+    );
+
+    let iter = CtfIterator::new(LoggingLevel::Fatal, &params).unwrap();
+    assert_eq!(
+        iter.component_logging_levels(),
+        ComponentLoggingLevels {
+            source: LoggingLevel::Warn,
+            muxer: LoggingLevel::Fatal,
+            trimmer: None,
+            proxy_sink: LoggingLevel::Fatal,
+        }
+    );
+}
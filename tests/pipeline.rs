@@ -0,0 +1,246 @@
+#![deny(warnings, clippy::all)]
+
+use babeltrace2_sys::{
+    BtResult, CtfPluginSourceFsInitParams, Error, Filter, LoggingLevel, Message, MessageType,
+    PipelineBuilder, PipelineComponent, RunStatus, SelfComponentFilter, SelfComponentSink,
+    SelfMessageIterator, Sink, Value, ValueRef,
+};
+use common::{init_logging, write_trace, Packet};
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::path::Path;
+use std::sync::Mutex;
+
+mod common;
+
+babeltrace2_sys::register_static_plugins!(
+    sink descriptors::panic_sink,
+    filter descriptors::fan_in_filter
+);
+
+#[allow(dead_code)]
+mod descriptors {
+    use super::*;
+    babeltrace2_sys::sink_plugin_descriptors!(panic_sink, "panic_sink", "sink", PanicSink);
+    babeltrace2_sys::filter_plugin_descriptors!(fan_in_filter, "fan_in", "filter", FanInFilter);
+}
+
+/// Input port index of each event received by the fan-in filter
+static FANNED_IN: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+struct PanicSink;
+
+impl Sink for PanicSink {
+    fn initialize(component: &mut SelfComponentSink, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+        component.add_input_port(&CString::new("in")?)?;
+        Ok(PanicSink)
+    }
+
+    fn consume(&mut self, _input_port: u64, _messages: &[Message]) -> BtResult<RunStatus> {
+        panic!("consume panicked");
+    }
+}
+
+/// Records the input port of each event and drops all of the messages
+struct FanInFilter;
+
+impl Filter for FanInFilter {
+    fn initialize(component: &mut SelfComponentFilter, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+        component.add_input_port(&CString::new("in0")?)?;
+        component.add_input_port(&CString::new("in1")?)?;
+        component.add_output_port(&CString::new("out")?)?;
+        Ok(FanInFilter)
+    }
+
+    fn next(
+        &mut self,
+        _msg_iter: &mut SelfMessageIterator,
+        input_port: u64,
+        messages: Vec<Message>,
+        _output: &mut VecDeque<Message>,
+    ) -> BtResult<()> {
+        let mut fanned_in = FANNED_IN.lock().unwrap();
+        for msg in messages.iter() {
+            if msg.get_type() == MessageType::Event {
+                fanned_in.push(input_port);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fs_params(trace_dir: &Path) -> CtfPluginSourceFsInitParams {
+    let input = CString::new(trace_dir.to_str().unwrap()).unwrap();
+    CtfPluginSourceFsInitParams::new(None, None, None, None, &[input.as_c_str()]).unwrap()
+}
+
+#[test]
+fn build_and_run_graph() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(td.path(), &[Packet::new(0, vec![(1, "a"), (2, "b")])]);
+    let input = CString::new(td.path().to_str().unwrap()).unwrap();
+    let params =
+        CtfPluginSourceFsInitParams::new(None, None, None, None, &[input.as_c_str()]).unwrap();
+
+    let mut builder = PipelineBuilder::new(LoggingLevel::None).unwrap();
+    builder
+        .add_source("ctf", "fs", "src", Some(params.params()))
+        .unwrap()
+        .add_filter("utils", "muxer", "muxer", None)
+        .unwrap()
+        .add_sink("utils", "dummy", "dummy", None)
+        .unwrap()
+        .connect_all("src", "muxer")
+        .unwrap()
+        .connect("muxer", 0, "dummy", "in")
+        .unwrap();
+
+    assert!(matches!(
+        builder.component("src"),
+        Some(PipelineComponent::Source(_))
+    ));
+    assert!(params.params().is_frozen());
+
+    let mut pipeline = builder.build();
+    pipeline.run().unwrap();
+}
+
+#[test]
+fn graph_build_errors() {
+    init_logging();
+
+    let mut builder = PipelineBuilder::new(LoggingLevel::None).unwrap();
+    builder
+        .add_filter("utils", "muxer", "muxer", None)
+        .unwrap()
+        .add_sink("utils", "dummy", "dummy", None)
+        .unwrap();
+
+    assert_eq!(
+        builder.add_sink("utils", "dummy", "dummy", None).err(),
+        Some(Error::DuplicateComponentName("dummy".to_owned()))
+    );
+    assert_eq!(
+        builder
+            .add_sink("nope", "dummy", "nope", None)
+            .err()
+            .map(|e| e.kind().clone()),
+        Some(Error::StaticPluginNotRegistered("nope".to_owned()))
+    );
+    assert_eq!(
+        builder
+            .add_filter("utils", "nope", "nope", None)
+            .err()
+            .map(|e| e.kind().clone()),
+        Some(Error::ComponentClassNotFound(
+            "filter.utils.nope".to_owned()
+        ))
+    );
+    assert_eq!(
+        builder.connect("dummy", 0, "muxer", 0).err(),
+        Some(Error::NoPortsOfKind("sink", "output"))
+    );
+    assert_eq!(
+        builder.connect("muxer", 0, "missing", 0).err(),
+        Some(Error::ComponentNotFound("missing".to_owned()))
+    );
+    assert_eq!(
+        builder.connect("muxer", "nope", "dummy", "in").err(),
+        Some(Error::PortNotFound(
+            "muxer".to_owned(),
+            "filter",
+            "output",
+            "'nope'".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn frozen_params() {
+    init_logging();
+
+    let key = CString::new("key").unwrap();
+    let mut params = Value::new_map().unwrap();
+    let mut inputs = Value::new_array().unwrap();
+    inputs.append_string_element(&key).unwrap();
+    assert_eq!(
+        inputs.insert_entry(&key, &Value::new_bool_with(true).unwrap()),
+        Err(Error::ValueTypeMismatch("map"))
+    );
+    params.insert_entry(&key, &inputs).unwrap();
+    assert_eq!(inputs.append_string_element(&key), Err(Error::ValueFrozen));
+
+    let mut builder = PipelineBuilder::new(LoggingLevel::None).unwrap();
+    builder
+        .add_sink("utils", "dummy", "dummy", Some(&params))
+        .unwrap();
+    assert_eq!(
+        params.insert_entry(&key, &Value::new_bool_with(true).unwrap()),
+        Err(Error::ValueFrozen)
+    );
+}
+
+#[test]
+fn component_panic_is_an_error() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    write_trace(td.path(), &[Packet::new(0, vec![(1, "a")])]);
+    let params = fs_params(td.path());
+
+    let mut builder = PipelineBuilder::new(LoggingLevel::None).unwrap();
+    builder
+        .add_source("ctf", "fs", "src", Some(params.params()))
+        .unwrap()
+        .add_filter("utils", "muxer", "muxer", None)
+        .unwrap()
+        .add_sink("panic_sink", "sink", "sink", None)
+        .unwrap()
+        .connect_all("src", "muxer")
+        .unwrap()
+        .connect("muxer", 0, "sink", "in")
+        .unwrap();
+    let err = builder.build().run().unwrap_err();
+    let panic_msg = Error::ComponentPanic("consume panicked".to_owned()).to_string();
+    assert!(err
+        .causes()
+        .expect("The error should carry the libbabeltrace2 causes")
+        .iter()
+        .any(|c| c.message.contains(&panic_msg)));
+}
+
+#[test]
+fn filter_fan_in() {
+    init_logging();
+
+    let td0 = tempfile::tempdir().unwrap();
+    write_trace(td0.path(), &[Packet::new(0, vec![(1, "a"), (2, "b")])]);
+    let params0 = fs_params(td0.path());
+    let td1 = tempfile::tempdir().unwrap();
+    write_trace(td1.path(), &[Packet::new(0, vec![(3, "c")])]);
+    let params1 = fs_params(td1.path());
+
+    let mut builder = PipelineBuilder::new(LoggingLevel::None).unwrap();
+    builder
+        .add_source("ctf", "fs", "src0", Some(params0.params()))
+        .unwrap()
+        .add_source("ctf", "fs", "src1", Some(params1.params()))
+        .unwrap()
+        .add_filter("fan_in", "filter", "fan_in", None)
+        .unwrap()
+        .add_sink("utils", "dummy", "dummy", None)
+        .unwrap()
+        .connect("src0", 0, "fan_in", "in0")
+        .unwrap()
+        .connect("src1", 0, "fan_in", "in1")
+        .unwrap()
+        .connect("fan_in", "out", "dummy", "in")
+        .unwrap();
+    builder.build().run().unwrap();
+
+    let mut fanned_in = FANNED_IN.lock().unwrap().clone();
+    fanned_in.sort_unstable();
+    assert_eq!(fanned_in, vec![0, 0, 1]);
+}
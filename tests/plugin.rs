@@ -0,0 +1,126 @@
+#![deny(warnings, clippy::all)]
+
+use babeltrace2_sys::{ComponentClassType, Error, Plugin, PluginLocations, PluginSet};
+use common::init_logging;
+use std::ffi::CString;
+use std::fs;
+
+mod common;
+
+babeltrace2_sys::register_static_plugins!();
+
+#[test]
+fn load_from_empty_locations() {
+    init_logging();
+
+    let td = tempfile::tempdir().unwrap();
+    let plugins = PluginSet::load_from_dir(td.path(), true, true).unwrap();
+    assert!(plugins.is_empty());
+    assert_eq!(plugins.len(), 0);
+    assert!(plugins.plugin(0).is_none());
+    assert_eq!(plugins.plugins().count(), 0);
+
+    let not_a_plugin = td.path().join("notes.txt");
+    fs::write(&not_a_plugin, "not a plugin").unwrap();
+    let plugins = PluginSet::load_from_file(&not_a_plugin, true).unwrap();
+    assert!(plugins.is_empty());
+}
+
+#[test]
+fn find_static_plugins() {
+    init_logging();
+
+    let plugins = PluginSet::find_all(PluginLocations::STATICS, true).unwrap();
+    let names: Vec<String> = plugins.plugins().map(|p| p.name().unwrap()).collect();
+    assert!(names.iter().any(|n| n == "ctf"));
+    assert!(names.iter().any(|n| n == "utils"));
+    assert!(plugins.plugin(plugins.len()).is_none());
+
+    let utils = Plugin::find(
+        &CString::new("utils").unwrap(),
+        PluginLocations::STATICS,
+        true,
+    )
+    .unwrap();
+    assert_eq!(utils.name().unwrap(), "utils");
+    assert_eq!(utils.path().unwrap(), None);
+}
+
+#[test]
+fn find_missing_plugin() {
+    init_logging();
+
+    let name = CString::new("nope").unwrap();
+    assert_eq!(
+        Plugin::find(&name, PluginLocations::STATICS, false).err(),
+        Some(Error::StaticPluginNotRegistered("nope".to_owned()))
+    );
+    assert_eq!(
+        Plugin::find(
+            &name,
+            PluginLocations {
+                std_env_var: true,
+                statics: true,
+                ..Default::default()
+            },
+            false
+        )
+        .err(),
+        Some(Error::PluginNotFound("nope".to_owned()))
+    );
+}
+
+#[test]
+fn component_classes() {
+    init_logging();
+
+    let utils = Plugin::find(
+        &CString::new("utils").unwrap(),
+        PluginLocations::STATICS,
+        true,
+    )
+    .unwrap();
+    let filters: Vec<String> = utils
+        .filter_component_classes()
+        .map(|c| {
+            assert_eq!(c.class_type(), ComponentClassType::Filter);
+            c.name().unwrap()
+        })
+        .collect();
+    assert!(filters.iter().any(|n| n == "muxer"));
+    assert!(filters.iter().any(|n| n == "trimmer"));
+    assert!(utils
+        .sink_component_classes()
+        .any(|c| c.name().unwrap() == "dummy"));
+    assert_eq!(utils.source_component_classes().count(), 0);
+
+    let ctf = Plugin::find(
+        &CString::new("ctf").unwrap(),
+        PluginLocations::STATICS,
+        true,
+    )
+    .unwrap();
+    let fs = ctf
+        .borrow_source_component_class_by_name(&CString::new("fs").unwrap())
+        .unwrap();
+    assert_eq!(fs.class_type(), ComponentClassType::Source);
+    assert_eq!(fs.name().unwrap(), "fs");
+    assert!(fs.description().unwrap().is_some());
+    let sink = ctf
+        .borrow_sink_component_class_by_name(&CString::new("fs").unwrap())
+        .unwrap();
+    assert_eq!(sink.class_type(), ComponentClassType::Sink);
+    assert!(ctf
+        .source_component_classes()
+        .any(|c| c.name().unwrap() == "lttng-live"));
+
+    let nope = CString::new("nope").unwrap();
+    assert_eq!(
+        ctf.borrow_filter_component_class_by_name(&nope).err(),
+        Some(Error::ResourceBorrow)
+    );
+    assert_eq!(
+        ctf.borrow_source_component_class_by_name(&nope).err(),
+        Some(Error::ResourceBorrow)
+    );
+}
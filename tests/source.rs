@@ -0,0 +1,424 @@
+#![deny(warnings, clippy::all)]
+
+use babeltrace2_sys::{
+    BtResult, ClockClass, ClockClassProperties, DisplayBase, Error, EventClassHandle, EventFields,
+    IntegerFieldClass, LoggingLevel, Message, MessageType, NextStatus, OwnedEvent, OwnedField,
+    OwnedFieldClass, PacketHandle, PipelineBuilder, RunStatus, ScalarField, SelfComponentSink,
+    SelfComponentSource, SelfMessageIterator, Sink, Source, StreamClassHandle, StreamClassParams,
+    StreamHandle, StructureMemberClass, TraceClass, TraceHandle, ValueRef,
+};
+use common::init_logging;
+use ordered_float::OrderedFloat;
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::sync::Mutex;
+
+mod common;
+
+babeltrace2_sys::register_static_plugins!(
+    source descriptors::test_source,
+    sink descriptors::test_sink,
+    source descriptors::mismatch_source
+);
+
+#[allow(dead_code)]
+mod descriptors {
+    use super::*;
+    babeltrace2_sys::source_plugin_descriptors!(test_source, "test_source", "source", TestSource);
+    babeltrace2_sys::sink_plugin_descriptors!(test_sink, "test_sink", "sink", TestSink);
+    babeltrace2_sys::source_plugin_descriptors!(
+        mismatch_source,
+        "mismatch_source",
+        "source",
+        MismatchSource
+    );
+}
+
+/// Events received by the sink, along with their input port index
+static RECEIVED: Mutex<Vec<(u64, OwnedEvent)>> = Mutex::new(Vec::new());
+
+/// Result of creating a stream class with packet clock snapshots but no clock
+static CLOCKLESS_STREAM_CLASS: Mutex<Option<Error>> = Mutex::new(None);
+
+/// Errors of the packet and clock snapshot mismatches made by the mismatch source
+static MISMATCHES: Mutex<Vec<Option<Error>>> = Mutex::new(Vec::new());
+
+fn int_class() -> IntegerFieldClass {
+    IntegerFieldClass {
+        field_value_range: 64,
+        preferred_display_base: DisplayBase::Decimal,
+    }
+}
+
+fn member_class(name: &str, class: OwnedFieldClass) -> StructureMemberClass {
+    StructureMemberClass {
+        name: Some(name.to_owned()),
+        class,
+    }
+}
+
+fn payload_class() -> OwnedFieldClass {
+    OwnedFieldClass::Structure(vec![
+        member_class("u", OwnedFieldClass::UnsignedInteger(int_class())),
+        member_class("i", OwnedFieldClass::SignedInteger(int_class())),
+        member_class("b", OwnedFieldClass::Bool),
+        member_class("d", OwnedFieldClass::DoublePrecisionReal),
+        member_class("s", OwnedFieldClass::String),
+        member_class(
+            "a",
+            OwnedFieldClass::StaticArray(
+                Box::new(OwnedFieldClass::UnsignedInteger(int_class())),
+                2,
+            ),
+        ),
+    ])
+}
+
+fn scalar(name: &str, value: ScalarField) -> OwnedField {
+    OwnedField::Scalar(Some(name.to_owned()), value)
+}
+
+fn payload_members(n: u64) -> Vec<OwnedField> {
+    vec![
+        scalar("u", ScalarField::UnsignedInteger(n)),
+        scalar("i", ScalarField::SignedInteger(-(n as i64))),
+        scalar("b", ScalarField::Bool(n & 1 == 0)),
+        scalar(
+            "d",
+            ScalarField::DoublePrecisionReal(OrderedFloat(n as f64 / 2.0)),
+        ),
+        scalar("s", ScalarField::String(format!("event {}", n))),
+        OwnedField::Array(
+            Some("a".to_owned()),
+            vec![
+                OwnedField::Scalar(None, ScalarField::UnsignedInteger(n)),
+                OwnedField::Scalar(None, ScalarField::UnsignedInteger(n + 1)),
+            ],
+        ),
+    ]
+}
+
+const NUM_EVENTS: u64 = 3;
+
+struct TestSource {
+    _trace_class: TraceClass,
+    _clock: ClockClass,
+    _stream_class: StreamClassHandle,
+    event_class: EventClassHandle,
+    _trace: TraceHandle,
+    stream: StreamHandle,
+    done: bool,
+}
+
+impl Source for TestSource {
+    fn initialize(component: &mut SelfComponentSource, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+        component.add_output_port(&CString::new("out")?)?;
+
+        let mut trace_class = TraceClass::new(component)?;
+        let clock = ClockClass::new(
+            component,
+            &ClockClassProperties {
+                frequency: 1_000_000_000,
+                offset_seconds: 0,
+                offset_cycles: 0,
+                precision: 0,
+                unix_epoch_origin: true,
+                name: Some("default".to_owned()),
+                description: None,
+                uuid: None,
+            },
+        )?;
+
+        *CLOCKLESS_STREAM_CLASS.lock().unwrap() = trace_class
+            .create_stream_class(&StreamClassParams {
+                supports_packets: true,
+                packets_have_clock_snapshots: true,
+                ..Default::default()
+            })
+            .err();
+
+        let stream_class = trace_class.create_stream_class(&StreamClassParams {
+            name: Some("stream"),
+            clock: Some(&clock),
+            ..Default::default()
+        })?;
+        let event_class =
+            trace_class.create_event_class(&stream_class, "ev", None, Some(&payload_class()))?;
+        let mut trace = trace_class.create_trace(Some("trace"))?;
+        let stream = trace.create_stream(&stream_class)?;
+
+        Ok(TestSource {
+            _trace_class: trace_class,
+            _clock: clock,
+            _stream_class: stream_class,
+            event_class,
+            _trace: trace,
+            stream,
+            done: false,
+        })
+    }
+
+    fn next(
+        &mut self,
+        msg_iter: &mut SelfMessageIterator,
+        output: &mut VecDeque<Message>,
+    ) -> BtResult<NextStatus> {
+        if self.done {
+            return Ok(NextStatus::End);
+        }
+        let stream = self.stream.stream();
+        output.push_back(msg_iter.create_stream_beginning_message(&stream)?);
+        for n in 0..NUM_EVENTS {
+            let payload = OwnedField::Structure(None, payload_members(n));
+            output.push_back(msg_iter.create_event_message(
+                &self.event_class.event_class(),
+                &stream,
+                None,
+                Some(n * 10),
+                &EventFields {
+                    payload: Some(&payload),
+                    ..Default::default()
+                },
+            )?);
+        }
+        output.push_back(msg_iter.create_stream_end_message(&stream)?);
+        self.done = true;
+        Ok(NextStatus::End)
+    }
+}
+
+struct TestSink;
+
+impl Sink for TestSink {
+    fn initialize(component: &mut SelfComponentSink, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+        component.add_input_port(&CString::new("in")?)?;
+        Ok(TestSink)
+    }
+
+    fn consume(&mut self, input_port: u64, messages: &[Message]) -> BtResult<RunStatus> {
+        let mut received = RECEIVED.lock().unwrap();
+        for msg in messages.iter() {
+            if msg.get_type() == MessageType::Event {
+                received.push((input_port, msg.borrow_event()?.to_owned()?));
+            }
+        }
+        Ok(RunStatus::Ok)
+    }
+}
+
+/// Creates messages that don't match their stream class, recording the errors
+struct MismatchSource {
+    _trace_class: TraceClass,
+    _clock: ClockClass,
+    _stream_classes: Vec<StreamClassHandle>,
+    event_class: EventClassHandle,
+    packet_event_class: EventClassHandle,
+    clockless_event_class: EventClassHandle,
+    _trace: TraceHandle,
+    stream: StreamHandle,
+    packet_stream: StreamHandle,
+    clockless_stream: StreamHandle,
+}
+
+impl Source for MismatchSource {
+    fn initialize(component: &mut SelfComponentSource, _: Option<ValueRef<'_>>) -> BtResult<Self> {
+        component.add_output_port(&CString::new("out")?)?;
+
+        let mut trace_class = TraceClass::new(component)?;
+        let clock = ClockClass::new(
+            component,
+            &ClockClassProperties {
+                frequency: 1_000_000_000,
+                offset_seconds: 0,
+                offset_cycles: 0,
+                precision: 0,
+                unix_epoch_origin: true,
+                name: Some("default".to_owned()),
+                description: None,
+                uuid: None,
+            },
+        )?;
+
+        let mut mismatches = MISMATCHES.lock().unwrap();
+        mismatches.push(
+            trace_class
+                .create_stream_class(&StreamClassParams {
+                    clock: Some(&clock),
+                    packets_have_clock_snapshots: true,
+                    ..Default::default()
+                })
+                .err(),
+        );
+
+        let stream_class = trace_class.create_stream_class(&StreamClassParams {
+            clock: Some(&clock),
+            ..Default::default()
+        })?;
+        let packet_stream_class = trace_class.create_stream_class(&StreamClassParams {
+            clock: Some(&clock),
+            supports_packets: true,
+            packets_have_clock_snapshots: true,
+            ..Default::default()
+        })?;
+        let clockless_stream_class = trace_class.create_stream_class(&Default::default())?;
+        let event_class = trace_class.create_event_class(&stream_class, "ev", None, None)?;
+        let packet_event_class =
+            trace_class.create_event_class(&packet_stream_class, "ev", None, None)?;
+        let clockless_event_class =
+            trace_class.create_event_class(&clockless_stream_class, "ev", None, None)?;
+        let mut trace = trace_class.create_trace(None)?;
+        let stream = trace.create_stream(&stream_class)?;
+        let packet_stream = trace.create_stream(&packet_stream_class)?;
+        let clockless_stream = trace.create_stream(&clockless_stream_class)?;
+
+        mismatches.push(PacketHandle::new(&stream.stream()).err());
+
+        Ok(MismatchSource {
+            _trace_class: trace_class,
+            _clock: clock,
+            _stream_classes: vec![stream_class, packet_stream_class, clockless_stream_class],
+            event_class,
+            packet_event_class,
+            clockless_event_class,
+            _trace: trace,
+            stream,
+            packet_stream,
+            clockless_stream,
+        })
+    }
+
+    fn next(
+        &mut self,
+        msg_iter: &mut SelfMessageIterator,
+        _output: &mut VecDeque<Message>,
+    ) -> BtResult<NextStatus> {
+        let fields = EventFields::default();
+        let stream = self.stream.stream();
+        let packet_stream = self.packet_stream.stream();
+        let packet = PacketHandle::new(&packet_stream)?;
+
+        let mut mismatches = MISMATCHES.lock().unwrap();
+        mismatches.push(
+            msg_iter
+                .create_packet_beginning_message(&packet.packet(), None)
+                .err(),
+        );
+        mismatches.push(
+            msg_iter
+                .create_packet_end_message(&packet.packet(), None)
+                .err(),
+        );
+        mismatches.push(
+            msg_iter
+                .create_event_message(
+                    &self.event_class.event_class(),
+                    &stream,
+                    Some(&packet.packet()),
+                    Some(0),
+                    &fields,
+                )
+                .err(),
+        );
+        mismatches.push(
+            msg_iter
+                .create_event_message(
+                    &self.packet_event_class.event_class(),
+                    &packet_stream,
+                    None,
+                    Some(0),
+                    &fields,
+                )
+                .err(),
+        );
+        mismatches.push(
+            msg_iter
+                .create_event_message(
+                    &self.event_class.event_class(),
+                    &stream,
+                    None,
+                    None,
+                    &fields,
+                )
+                .err(),
+        );
+        mismatches.push(
+            msg_iter
+                .create_event_message(
+                    &self.clockless_event_class.event_class(),
+                    &self.clockless_stream.stream(),
+                    None,
+                    Some(0),
+                    &fields,
+                )
+                .err(),
+        );
+        Ok(NextStatus::End)
+    }
+}
+
+#[test]
+fn source_emits_events() {
+    init_logging();
+
+    let mut builder = PipelineBuilder::new(LoggingLevel::None).unwrap();
+    builder
+        .add_source("test_source", "source", "src", None)
+        .unwrap()
+        .add_sink("test_sink", "sink", "sink", None)
+        .unwrap()
+        .connect("src", "out", "sink", "in")
+        .unwrap();
+    builder.build().run().unwrap();
+
+    assert_eq!(
+        CLOCKLESS_STREAM_CLASS.lock().unwrap().take(),
+        Some(Error::PacketClockSnapshotsRequireClock)
+    );
+
+    let received = RECEIVED.lock().unwrap();
+    assert_eq!(received.len(), NUM_EVENTS as usize);
+    for (n, (input_port, event)) in received.iter().enumerate() {
+        let n = n as u64;
+        assert_eq!(*input_port, 0);
+        assert_eq!(event.class_properties.name.as_deref(), Some("ev"));
+        assert_eq!(event.clock_snapshot, Some(n as i64 * 10));
+        let payload = event.properties.payload.as_ref().unwrap();
+        assert_eq!(
+            payload.as_structure().unwrap(),
+            payload_members(n).as_slice()
+        );
+        assert_eq!(
+            event.properties.get("payload.s").unwrap().as_str(),
+            Ok(format!("event {}", n).as_str())
+        );
+    }
+}
+
+#[test]
+fn message_stream_class_mismatches() {
+    init_logging();
+
+    let mut builder = PipelineBuilder::new(LoggingLevel::None).unwrap();
+    builder
+        .add_source("mismatch_source", "source", "src", None)
+        .unwrap()
+        .add_sink("utils", "dummy", "dummy", None)
+        .unwrap()
+        .connect("src", "out", "dummy", "in")
+        .unwrap();
+    builder.build().run().unwrap();
+
+    assert_eq!(
+        *MISMATCHES.lock().unwrap(),
+        vec![
+            Some(Error::PacketClockSnapshotsRequirePackets),
+            Some(Error::PacketsNotSupported),
+            Some(Error::ClockSnapshotRequired),
+            Some(Error::ClockSnapshotRequired),
+            Some(Error::PacketsNotSupported),
+            Some(Error::PacketRequired),
+            Some(Error::ClockSnapshotRequired),
+            Some(Error::UnexpectedClockSnapshot),
+        ]
+    );
+}